@@ -0,0 +1,38 @@
+//! Compares looking up rows by their primary key with a binary search against a linear scan, on a
+//! *CustomAttribute* table the size of a framework assembly's. Run with `cargo bench`.
+
+use std::{hint::black_box, time::Instant};
+
+use rscil::*;
+
+const ROWS: u32 = 50_000;
+const LOOKUPS: u32 = 1_000;
+
+fn main() {
+    // Four attributes on each type, sorted by parent like a compiler emits them.
+    let table: Vec<Row> = (1..=ROWS).map(|rid| Row::CustomAttribute(CustomAttribute {
+        index: rid,
+        parent: CodedIndex::from(TableKind::TypeDef, (rid - 1) / 4 + 1),
+        type_: CodedIndex::from(TableKind::MemberRef, 1),
+        value: BlobIndex(0),
+    })).collect();
+    let targets: Vec<u32> = (0..LOOKUPS)
+        .filter_map(|i| CodedIndex::from(TableKind::TypeDef, i * (ROWS / 4 / LOOKUPS) + 1).encode(CodedIndexTag::HasCustomAttribute))
+        .collect();
+
+    let start = Instant::now();
+    for target in &targets {
+        black_box(find_range_by_key(&table, Row::sort_key, *target));
+    }
+    let binary = start.elapsed();
+
+    let start = Instant::now();
+    for target in &targets {
+        black_box(table.iter().filter(|row| row.sort_key() == Some(*target)).count());
+    }
+    let linear = start.elapsed();
+
+    println!("{} lookups in {} rows", targets.len(), ROWS);
+    println!("binary search: {:?}", binary);
+    println!("linear scan:   {:?}", linear);
+}
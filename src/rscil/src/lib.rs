@@ -6,6 +6,108 @@ pub use metadata::*;
 mod tests {
     use super::*;
 
+    /// Returns the file offset of row `rid` of the `kind` table in the #~ stream of `image`.
+    fn row_offset(image: &PeImage, kind: TableKind, rid: u32) -> usize {
+        let metadata = &image.streams.metadata;
+        let context = metadata.context();
+        let stream = image.metadata_header.stream_headers.iter().find(|header| header.name == "#~").unwrap();
+        let root = image.rva_to_offset(image.cli_header.meta_data.rva).unwrap() as usize;
+
+        root + stream.offset as usize + 24 + 4 * metadata.rows.len()
+            + context.table_offset(kind) + (rid as usize - 1) * kind.row_size(context)
+    }
+
+    /// Appends `stream` to the end of the image file `data` and points the #~ stream header at it, renamed
+    /// to `name`.
+    fn replace_tables_stream(image: &PeImage, mut data: Vec<u8>, name: &str, stream: Vec<u8>) -> Vec<u8> {
+        let root = image.rva_to_offset(image.cli_header.meta_data.rva).unwrap() as usize;
+        let header = data[root..].windows(4).position(|name| name == b"#~\0\0").unwrap() + root;
+
+        let offset = (data.len() - root) as u32;
+        data[header - 8..header - 4].copy_from_slice(&offset.to_le_bytes());
+        data[header - 4..header].copy_from_slice(&(stream.len() as u32).to_le_bytes());
+        data[header..header + 2].copy_from_slice(name.as_bytes());
+        data.extend(stream);
+        data
+    }
+
+    /// Rebuilds the #~ stream of the image file `data` with the rows of `tables` added as new tables. A
+    /// present table is replaced by its entry in `tables`, and removed if that has no rows. The tables must
+    /// be small enough not to widen any index.
+    fn add_tables(image: &PeImage, data: Vec<u8>, tables: Vec<(TableKind, Vec<Vec<u8>>)>) -> Vec<u8> {
+        let metadata = &image.streams.metadata;
+        let context = metadata.context();
+        let start = row_offset(image, TableKind::Module, 1) - 24 - 4 * metadata.rows.len();
+
+        let mut kinds: Vec<(TableKind, u32, Vec<u8>)> = metadata.present_tables().into_iter()
+            .filter(|kind| tables.iter().all(|(replaced, _)| replaced != kind))
+            .map(|kind| {
+                let offset = row_offset(image, kind, 1);
+                let count = context.get_row_count(kind);
+                (kind, count, data[offset..offset + count as usize * kind.row_size(context)].to_vec())
+            })
+            .collect();
+        kinds.extend(tables.into_iter()
+            .filter(|(_, rows)| !rows.is_empty())
+            .map(|(kind, rows)| (kind, rows.len() as u32, rows.concat())));
+        kinds.sort_by_key(|(kind, ..)| u8::from(*kind));
+
+        let mut stream = data[start..start + 8].to_vec();
+        stream.extend(kinds.iter().fold(0u64, |valid, (kind, ..)| valid | 1 << u8::from(*kind)).to_le_bytes());
+        stream.extend(metadata.sorted.to_le_bytes());
+        for (_, count, _) in &kinds {
+            stream.extend(count.to_le_bytes());
+        }
+        for (_, _, rows) in &kinds {
+            stream.extend(rows);
+        }
+        replace_tables_stream(image, data, "#~", stream)
+    }
+
+    /// HelloWorld.exe with a private Main, and with fields, properties and events added to the HelloWorld type:
+    ///
+    /// - Fields 1 to 4 are public, protected, private and internal.
+    /// - Property 1 has .ctor as getter, Main as setter and as other method. Property 2 has Main as getter.
+    /// - Event 1 has .ctor as add and other method, Main as remove and fire. Event 2 has Main as add.
+    fn members_image() -> PeImage {
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let image = PeParser::from_bytes("HelloWorld.exe", data.clone()).read().unwrap();
+        let name = (image.get_type_def(2).unwrap().type_name.0 as u16).to_le_bytes();
+
+        let flags = row_offset(&image, TableKind::MethodDef, 1) + 6;
+        let private = u16::from(image.get_method_def(1).unwrap().flags) & !0x7 | 0x1;
+        data[flags..flags + 2].copy_from_slice(&private.to_le_bytes());
+
+        // Flags, Name and Signature, Type or EventType, which is System.Object for events.
+        let member = |flags: u16, signature: u16| [&flags.to_le_bytes()[..], &name, &signature.to_le_bytes()].concat();
+        // Semantics, Method and Association, which is (row << 1) | 0 for events and | 1 for properties.
+        let semantics = |semantics: u16, method: u16, association: u16| {
+            [semantics.to_le_bytes(), method.to_le_bytes(), association.to_le_bytes()].concat()
+        };
+        let (main, ctor) = (1, 2);
+
+        let data = add_tables(&image, data, vec![
+            (TableKind::Field, vec![member(0x6, 0), member(0x4, 0), member(0x1, 0), member(0x3, 0)]),
+            (TableKind::EventMap, vec![[2u16.to_le_bytes(), 1u16.to_le_bytes()].concat()]),
+            (TableKind::Event, vec![member(0, 0x05), member(0, 0x05)]),
+            (TableKind::PropertyMap, vec![[2u16.to_le_bytes(), 1u16.to_le_bytes()].concat()]),
+            (TableKind::Property, vec![member(0, 0), member(0, 0)]),
+            // Sorted by Association.
+            (TableKind::MethodSemantics, vec![
+                semantics(0x08, ctor, 1 << 1),
+                semantics(0x10, main, 1 << 1),
+                semantics(0x20, main, 1 << 1),
+                semantics(0x04, ctor, 1 << 1),
+                semantics(0x02, ctor, 1 << 1 | 1),
+                semantics(0x01, main, 1 << 1 | 1),
+                semantics(0x04, main, 1 << 1 | 1),
+                semantics(0x08, main, 2 << 1),
+                semantics(0x02, main, 2 << 1 | 1),
+            ]),
+        ]);
+        PeParser::from_bytes("HelloWorld.exe", data).read().unwrap()
+    }
+
     #[test]
     fn it_works() {
         let image = PeParser::open("tests/HelloWorld.exe")
@@ -15,9 +117,10 @@ mod tests {
         let assembly = image.get_assembly().unwrap();
         dbg!(image.get_string(assembly.name));
 
-        let entry_point_index = match image.cli_header.entry_point_token {
-            MetadataToken::Table(_, index) => index,
-            _ => panic!("Invalid entry point token"),
+        assert!(image.is_executable());
+        let entry_point_index = match image.entry_point_kind() {
+            EntryPointKind::MethodDef(index) => index,
+            kind => panic!("Invalid entry point: {:?}", kind),
         };
         dbg!(entry_point_index);
 
@@ -25,4 +128,1797 @@ mod tests {
 
         dbg!(entry_point);
     }
+
+    #[test]
+    fn images_are_send_and_sync() {
+        fn assert_sync<T: Send + Sync>() {}
+        assert_sync::<PeImage>();
+    }
+
+    #[test]
+    fn method_bodies_by_token() {
+        let data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let image = PeParser::from_bytes("HelloWorld.exe", data.clone()).read().unwrap();
+
+        let entry_point = image.get_method_body(1).unwrap();
+        assert!(std::ptr::eq(entry_point, image.entry_point_body().unwrap()));
+
+        let type_def = MetadataToken::table(TableKind::TypeDef, 1);
+        assert!(matches!(image.get_method_body_by_token(type_def), Err(LushError::InvalidToken(_))));
+        let missing = MetadataToken::table(TableKind::MethodDef, 100);
+        assert!(matches!(image.get_method_body_by_token(missing), Err(LushError::InvalidToken(_))));
+
+        // Like a facade assembly, without a MethodDef table at all.
+        let facade = PeParser::from_bytes("HelloWorld.exe", add_tables(&image, data, vec![(TableKind::MethodDef, vec![])])).read().unwrap();
+        assert!(facade.get_method_def(1).is_none());
+        assert!(matches!(facade.entry_point_body(), Err(LushError::InvalidToken(_))));
+        assert!(image.get_field(1).is_none());
+    }
+
+    #[test]
+    fn dump_tables() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let dump = image.dump_tables();
+        assert!(dump.starts_with("Module (0x00): 1 rows\n"));
+        assert!(dump.contains("\nTypeDef (0x02): 2 rows\n"));
+        assert!(dump.contains(concat!(
+            "  [2] flags: 0x00100001, type_name: \"HelloWorld\", type_namespace: \"\", extends: TypeRef[1], ",
+            "field_list: Field[1], method_list: MethodDef[1]\n",
+        )));
+        assert_eq!(dump.lines().filter(|line| !line.starts_with("  ")).count(), image.streams.metadata.present_tables().len());
+    }
+
+    #[test]
+    fn diff_of_same_image_is_empty() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let other = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let diff = image.diff(&other);
+        assert!(diff.is_empty());
+        assert!(!diff.mvid_changed);
+    }
+
+    #[test]
+    fn diff_compares_resolved_signatures() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let write_line = image.get_member_ref(3).unwrap();
+        assert_eq!(image.get_blob(write_line.signature), Some(&[0x00, 0x01, 0x01, 0x0E][..]));
+
+        let root = image.rva_to_offset(image.cli_header.meta_data.rva).unwrap() as usize;
+        let blobs = image.metadata_header.stream_headers.iter().find(|header| header.name == "#Blob").unwrap();
+        // The signature of Console.WriteLine(string), after its length byte.
+        let blob = root + blobs.offset as usize + write_line.signature.0 as usize + 1;
+        // The Signature column of Main, after RVA, ImplFlags, Flags and Name.
+        let signature = row_offset(&image, TableKind::MethodDef, 1) + 10;
+
+        // Main points at a signature returning System.Object, which is TypeRef 1.
+        let mut old = std::fs::read("tests/HelloWorld.exe").unwrap();
+        old[blob..blob + 4].copy_from_slice(&[0x00, 0x00, 0x12, 0x05]);
+        old[signature..signature + 2].copy_from_slice(&(write_line.signature.0 as u16).to_le_bytes());
+        // The same, with the first two TypeRef rows swapped so that System.Object is TypeRef 2.
+        let mut new = old.clone();
+        new[blob + 3] = 0x09;
+        let first = row_offset(&image, TableKind::TypeRef, 1);
+        let second = row_offset(&image, TableKind::TypeRef, 2);
+        let row = new[first..second].to_vec();
+        new.copy_within(second..second + row.len(), first);
+        new[second..second + row.len()].copy_from_slice(&row);
+
+        let old = PeParser::from_bytes("old", old).read().unwrap();
+        let new = PeParser::from_bytes("new", new).read().unwrap();
+
+        let changed = image.diff(&old);
+        let methods = changed.tables.iter().find(|table| table.kind == TableKind::MethodDef).unwrap();
+        assert_eq!(methods.changed, ["HelloWorld::Main"]);
+
+        // TypeRef rows are compared by position, the signature referencing one of them by its name.
+        let moved = old.diff(&new);
+        assert!(moved.tables.iter().any(|table| table.kind == TableKind::TypeRef));
+        assert!(!moved.tables.iter().any(|table| table.kind == TableKind::MethodDef));
+    }
+
+    #[test]
+    fn public_surface() {
+        let image = members_image();
+        let program = image.get_type_def(2).unwrap();
+        assert_eq!(image.fields_of(program).len(), 4);
+
+        // Only the public .ctor, the public and protected fields, and the members with a public accessor.
+        let surface = image.public_surface(program);
+        assert_eq!(surface.methods.iter().map(|method| method.index).collect::<Vec<_>>(), [2]);
+        assert_eq!(surface.fields.iter().map(|field| field.index).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(surface.properties.iter().map(|property| property.index).collect::<Vec<_>>(), [1]);
+        assert_eq!(surface.events.iter().map(|event| event.index).collect::<Vec<_>>(), [1]);
+
+        let module = image.public_surface(image.get_type_def(1).unwrap());
+        assert!(module.methods.is_empty() && module.fields.is_empty());
+        assert!(module.properties.is_empty() && module.events.is_empty());
+    }
+
+    #[test]
+    fn branch_targets_resolve_to_instruction_indices() {
+        let mut body = MethodBody::tiny(5 << 2);
+        body.body = vec![
+            Instruction { opcode: OpCode::Nop(()), offset: 0 },
+            Instruction { opcode: OpCode::BrS(BranchTarget::new(1)), offset: 1 },
+            Instruction { opcode: OpCode::Nop(()), offset: 3 },
+            Instruction { opcode: OpCode::Ret(()), offset: 4 },
+        ];
+        body.resolve_targets().unwrap();
+
+        let targets = body.body[1].opcode.branch_targets();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].offset, 4);
+        assert_eq!(targets[0].index, Some(3));
+
+        // A target before the start of the method body is an error, and isn't listed as a target.
+        body.body[1].opcode = OpCode::BrS(BranchTarget::new(-4));
+        assert_eq!(body.resolve_targets().unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+        assert!(body.body[1].branch_targets().is_empty());
+    }
+
+    #[test]
+    fn decode_marshal_descriptors() {
+        assert_eq!(decode_marshal_descriptor(&[0x14]).unwrap(), MarshalDescriptor::Intrinsic(NativeType::LPStr));
+        assert_eq!(decode_marshal_descriptor(&[0x17, 0x80, 0x80]).unwrap(), MarshalDescriptor::ByValTStr { size: 128 });
+        assert_eq!(
+            decode_marshal_descriptor(&[0x1e, 0x10, 0x07]).unwrap(),
+            MarshalDescriptor::ByValArray { size: 16, element_type: Some(NativeType::I4) }
+        );
+        assert_eq!(
+            decode_marshal_descriptor(&[0x2a, 0x50, 0x01]).unwrap(),
+            MarshalDescriptor::LPArray { element_type: None, size_param_index: Some(1), size: None }
+        );
+        assert_eq!(
+            decode_marshal_descriptor(&[0x2a, 0x15, 0x02, 0x04]).unwrap(),
+            MarshalDescriptor::LPArray { element_type: Some(NativeType::LPWStr), size_param_index: Some(2), size: Some(4) }
+        );
+        assert!(decode_marshal_descriptor(&[0x17]).is_err());
+
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        assert_eq!(image.marshal_of(CodedIndex::from(TableKind::Param, 1)).unwrap(), None);
+        assert_eq!(image.marshal_of(CodedIndex::from(TableKind::TypeDef, 1)).unwrap(), None);
+    }
+
+    #[test]
+    fn guid_is_formatted_like_ildasm() {
+        // HelloWorld.exe's MVID as stored in its #GUID heap.
+        let guid = [
+            0xF5, 0x4F, 0x89, 0xA6, 0x15, 0xBC, 0xCE, 0x44,
+            0x80, 0x3F, 0x43, 0x99, 0x67, 0x2E, 0x56, 0x9A,
+        ];
+        assert_eq!(format_guid(guid), "{A6894FF5-BC15-44CE-803F-4399672E569A}");
+    }
+
+    #[test]
+    fn strict_read_accepts_hello_world() {
+        let options = ParseOptions { strict: true, ..ParseOptions::default() };
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .map_err(LushError::from)
+            .and_then(|parser| parser.read_with(options))
+            .unwrap();
+
+        assert!(image.get_method_body(1).is_some());
+        assert!(image.warnings().is_empty());
+    }
+
+    #[test]
+    fn module_mvid_resolves_through_guid_heap() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        assert_eq!(image.get_module().mvid, GuidIndex(1));
+        assert_eq!(image.streams.guids.iter().count(), 1);
+        assert_eq!(image.get_guid(GuidIndex(0)), None);
+        assert_eq!(format_guid(image.mvid().unwrap()), "{A6894FF5-BC15-44CE-803F-4399672E569A}");
+    }
+
+    #[test]
+    fn large_guid_heap_widens_module_row() {
+        use std::{collections::HashMap, io::Cursor};
+
+        let context = TableDecodeContext::new(HashMap::new(), HeapSizes::GUID_FLAG);
+        let mut buffer = Cursor::new(vec![
+            0x00, 0x00,             // Generation
+            0x0A, 0x00,             // Name
+            0x01, 0x00, 0x01, 0x00, // Mvid
+            0x02, 0x00, 0x00, 0x00, // EncId
+            0x03, 0x00, 0x00, 0x00, // EncBaseId
+        ]);
+
+        let module = Module::decode(&context, &mut buffer).unwrap();
+        assert_eq!(module.name, StringIndex(0x0A));
+        assert_eq!(module.mvid, GuidIndex(0x0001_0001));
+        assert_eq!(module.enc_id, GuidIndex(2));
+        assert_eq!(module.enc_base_id, GuidIndex(3));
+        assert_eq!(buffer.position(), 16);
+    }
+
+    #[test]
+    fn heap_index_sizes() {
+        use std::{collections::HashMap, io::Cursor};
+
+        let heap_sizes = HeapSizes::new(0x05);
+        assert!(heap_sizes.is_large_strings() && heap_sizes.is_large_blob() && !heap_sizes.is_large_guid());
+        assert_eq!((heap_sizes.string_index_size(), heap_sizes.guid_index_size(), heap_sizes.blob_index_size()), (4, 2, 4));
+
+        let context = TableDecodeContext::new(HashMap::new(), heap_sizes);
+        assert_eq!((StringIndex::size(&context), GuidIndex::size(&context), BlobIndex::size(&context)), (4, 2, 4));
+
+        let mut buffer = Cursor::new(vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+        assert_eq!(BlobIndex::decode(&context, &mut buffer).unwrap(), BlobIndex(0x04030201));
+        assert_eq!(GuidIndex::decode(&context, &mut buffer).unwrap(), GuidIndex(0x0605));
+
+        let context = TableDecodeContext::new(HashMap::new(), HeapSizes::new(0));
+        assert_eq!(StringIndex::decode(&context, &mut Cursor::new(vec![0x01, 0x02, 0x03, 0x04])).unwrap(), StringIndex(0x0201));
+    }
+
+    #[test]
+    fn type_spec_signatures() {
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let image = PeParser::from_bytes("HelloWorld.exe", data.clone()).read().unwrap();
+
+        // Overwrite the 8 byte public key token in blob 1 with GENERICINST CLASS TypeRef[1] <string, int32, uint8[]>.
+        let stream = image.metadata_header.stream_headers.iter().find(|header| header.name == "#Blob").unwrap();
+        let blob = image.rva_to_offset(image.cli_header.meta_data.rva).unwrap() as usize + stream.offset as usize + 2;
+        data[blob..blob + 8].copy_from_slice(&[0x15, 0x12, 0x05, 0x03, 0x0E, 0x08, 0x1D, 0x05]);
+
+        let data = add_tables(&image, data, vec![(TableKind::TypeSpec, vec![1u16.to_le_bytes().to_vec()])]);
+        let image = PeParser::from_bytes("HelloWorld.exe", data).read().unwrap();
+
+        let type_spec = image.table::<TypeSpec>().get(1).unwrap();
+        assert_eq!(image.get_type_spec(type_spec).unwrap(), SigType::GenericInst {
+            base: Box::new(SigType::Class(CodedIndex::from(TableKind::TypeRef, 1))),
+            args: vec![SigType::String, SigType::I4, SigType::SzArray(Box::new(SigType::U1))],
+        });
+
+        // A MemberRef on the TypeSpec is declared by the instantiated type.
+        let type_spec = CodedIndex::from(TableKind::TypeSpec, 1);
+        let mut member_ref = *image.get_member_ref(3).unwrap();
+        member_ref.class = type_spec;
+        let info = image.member_ref_info(&member_ref);
+        assert_eq!(info.declaring_type, image.type_full_name(type_spec));
+        assert!(info.declaring_type.unwrap().starts_with("[mscorlib]System.Object<"));
+    }
+
+    #[test]
+    fn method_signatures_of_hello_world() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let main = image.get_method_signature(image.get_method_def(1).unwrap()).unwrap();
+        assert!(!main.has_this);
+        assert_eq!(main.calling_convention, CallingConvention::Default);
+        assert_eq!(main.return_type, SigType::Void);
+        assert!(main.params.is_empty());
+
+        let ctor = image.get_method_signature(image.get_method_def(2).unwrap()).unwrap();
+        assert!(ctor.has_this);
+
+        let generic = MethodSignature::parse(&[0x30, 0x01, 0x01, 0x1e, 0x00, 0x1d, 0x0e], image.streams.metadata.context()).unwrap();
+        assert_eq!(generic.generic_param_count, 1);
+        assert_eq!(generic.return_type, SigType::MVar(0));
+        assert_eq!(generic.params, vec![SigType::SzArray(Box::new(SigType::String))]);
+
+        // A parameter count of 0x1FFFFFFF in a truncated blob fails instead of reserving room for it.
+        assert!(MethodSignature::parse(&[0x00, 0xDF, 0xFF, 0xFF, 0xFF, 0x01], image.streams.metadata.context()).is_err());
+
+        let main_body = image.get_method_body(1).unwrap();
+        assert_eq!(main_body.local_var_sig_token, None);
+        assert!(image.get_local_variables(main_body).unwrap().is_empty());
+
+        let locals = LocalVarSig::parse(&[0x07, 0x02, 0x08, 0x45, 0x0e], image.streams.metadata.context()).unwrap();
+        assert_eq!(locals.locals, vec![SigType::I4, SigType::Pinned(Box::new(SigType::String))]);
+        assert!(LocalVarSig::parse(&[0x07, 0xDF, 0xFF, 0xFF, 0xFF, 0x08], image.streams.metadata.context()).is_err());
+
+        // A StandAloneSig holds either locals or the void(int32) signature of a calli call site.
+        let context = image.streams.metadata.context();
+        assert_eq!(StandaloneSignature::parse(&[0x07, 0x01, 0x08], context).unwrap(), StandaloneSignature::LocalVars(vec![SigType::I4]));
+        let StandaloneSignature::Method(calli) = StandaloneSignature::parse(&[0x00, 0x01, 0x01, 0x08], context).unwrap() else {
+            panic!("Expected a method signature");
+        };
+        assert_eq!((calli.return_type, calli.params), (SigType::Void, vec![SigType::I4]));
+    }
+
+    #[test]
+    fn compressed_integers() {
+        let unsigned = |bytes: &[u8]| read_compressed_u32(&mut &bytes[..]).unwrap();
+        assert_eq!(unsigned(&[0x7F]), (0x7F, 1));
+        assert_eq!(unsigned(&[0x80, 0x80]), (0x80, 2));
+        assert_eq!(unsigned(&[0xBF, 0xFF]), (0x3FFF, 2));
+        assert_eq!(unsigned(&[0xC0, 0x00, 0x40, 0x00]), (0x4000, 4));
+        assert_eq!(unsigned(&[0xDF, 0xFF, 0xFF, 0xFF]), (0x1FFFFFFF, 4));
+
+        let error = read_compressed_u32(&mut &[0xE0u8, 0, 0, 0][..]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        let signed = |bytes: &[u8]| read_compressed_i32(&mut &bytes[..]).unwrap().0;
+        assert_eq!(signed(&[0x06]), 3);
+        assert_eq!(signed(&[0x7B]), -3);
+        assert_eq!(signed(&[0x80, 0x80]), 64);
+        assert_eq!(signed(&[0x01]), -64);
+        assert_eq!(signed(&[0xC0, 0x00, 0x40, 0x00]), 8192);
+        assert_eq!(signed(&[0x80, 0x01]), -8192);
+        assert_eq!(signed(&[0xDF, 0xFF, 0xFF, 0xFE]), 268435455);
+        assert_eq!(signed(&[0xC0, 0x00, 0x00, 0x01]), -268435456);
+    }
+
+    #[test]
+    fn exception_clauses_in_data_sections() {
+        let small: &[u8] = &[
+            0x81, 0x10, 0x00, 0x00,
+            0x02, 0x00, 0x01, 0x00, 0x05, 0x06, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00,
+            0x41, 0x1C, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00,
+            0x10, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x01,
+        ];
+        let clauses = MethodBody::read_sections(&mut std::io::Cursor::new(small)).unwrap();
+
+        assert_eq!(clauses, vec![
+            ExceptionClause {
+                flags: ExceptionClauseKind::Finally,
+                try_offset: 1,
+                try_length: 5,
+                handler_offset: 6,
+                handler_length: 3,
+                class_token_or_filter: 0,
+            },
+            ExceptionClause {
+                flags: ExceptionClauseKind::Exception,
+                try_offset: 0,
+                try_length: 0x10,
+                handler_offset: 0x10,
+                handler_length: 0x20,
+                class_token_or_filter: 0x01000001,
+            },
+        ]);
+    }
+
+    #[test]
+    fn branch_targets_are_absolute_offsets() {
+        let branch = Instruction {
+            offset: 0x10,
+            opcode: OpCode::parse(Code::BrS, &mut &[0xFEu8][..]).unwrap(),
+        };
+        assert_eq!(branch.opcode.encoded_size(), 2);
+        assert_eq!(branch.branch_targets(), vec![0x10]);
+
+        let switch = Instruction {
+            offset: 0x04,
+            opcode: OpCode::parse(Code::Switch, &mut &[0x02, 0, 0, 0, 0x00, 0, 0, 0, 0x08, 0, 0, 0][..]).unwrap(),
+        };
+        assert_eq!(switch.opcode.encoded_size(), 13);
+        assert_eq!(switch.branch_targets(), vec![0x11, 0x19]);
+
+        let call = OpCode::parse(Code::Call, &mut &[0x01, 0x00, 0x00, 0x0A][..]).unwrap();
+        assert_eq!(call.encoded_size(), 5);
+        assert!(call.branch_targets().is_empty());
+
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let main = image.get_method_body(1).unwrap();
+        let last = main.body.last().unwrap();
+        assert_eq!(last.offset + last.opcode.encoded_size(), main.code_size);
+    }
+
+    #[test]
+    fn basic_blocks() {
+        let mut body = MethodBody::tiny(18 << 2);
+        body.body = vec![
+            Instruction { opcode: OpCode::Ldarg0(()), offset: 0 },
+            Instruction { opcode: OpCode::parse(Code::Switch, &mut &[0x02, 0, 0, 0, 0x00, 0, 0, 0, 0x02, 0, 0, 0][..]).unwrap(), offset: 1 },
+            Instruction { opcode: OpCode::BrS(BranchTarget::new(1)), offset: 14 },
+            Instruction { opcode: OpCode::Nop(()), offset: 16 },
+            Instruction { opcode: OpCode::Ret(()), offset: 17 },
+        ];
+
+        assert_eq!(body.basic_blocks(), vec![
+            BasicBlock { start_offset: 0, instructions: 0..2, successors: vec![14, 16] },
+            BasicBlock { start_offset: 14, instructions: 2..3, successors: vec![17] },
+            BasicBlock { start_offset: 16, instructions: 3..4, successors: vec![17] },
+            BasicBlock { start_offset: 17, instructions: 4..5, successors: vec![] },
+        ]);
+
+        // Main of HelloWorld is straight-line code.
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        assert_eq!(image.get_method_body(1).unwrap().basic_blocks(), vec![
+            BasicBlock { start_offset: 0, instructions: 0..5, successors: vec![] },
+        ]);
+
+        // Protected blocks and handlers start blocks of their own.
+        let mut body = MethodBody::tiny(3 << 2);
+        body.body = vec![
+            Instruction { opcode: OpCode::Nop(()), offset: 0 },
+            Instruction { opcode: OpCode::Nop(()), offset: 1 },
+            Instruction { opcode: OpCode::Ret(()), offset: 2 },
+        ];
+        body.exception_clauses.push(ExceptionClause {
+            flags: ExceptionClauseKind::Fault,
+            try_offset: 0,
+            try_length: 1,
+            handler_offset: 1,
+            handler_length: 1,
+            class_token_or_filter: 0,
+        });
+        let blocks = body.basic_blocks();
+        assert_eq!(blocks.iter().map(|block| block.start_offset).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(blocks[0].successors, [1]);
+
+        // A handler that ends past u32::MAX doesn't overflow, and its end starts no block.
+        body.exception_clauses[0].handler_length = u32::MAX;
+        let blocks = body.basic_blocks();
+        assert_eq!(blocks.iter().map(|block| block.start_offset).collect::<Vec<_>>(), [0, 1]);
+    }
+
+    #[test]
+    fn one_and_two_byte_opcodes() {
+        let ceq = PeParser::read_code(&mut &[0xFE, 0x01][..], 0).unwrap();
+        assert_eq!(ceq.metadata().name, "ceq");
+        let add = PeParser::read_code(&mut &[0x58, 0xFE][..], 0).unwrap();
+        assert_eq!(add.metadata().name, "add");
+        let nop = PeParser::read_code(&mut &[0x00][..], 0).unwrap();
+        assert_eq!(nop.metadata().name, "nop");
+        assert!(PeParser::read_code(&mut &[0xFE][..], 0).is_err());
+
+        assert!(matches!(Code::from(&[0xFE, 0x01]), Some(Code::Ceq)));
+        assert!(matches!(Code::from(&[0xFF, 0x58]), Some(Code::Add)));
+        assert!(Code::from(&[0xFE, 0x10]).is_none());
+    }
+
+    #[test]
+    fn undefined_opcode_is_an_error() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // Replace the first opcode of Main with 0xA6, which is unused.
+        let main = image.get_method_def(1).unwrap();
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let header = image.rva_to_offset(main.rva).unwrap() as usize;
+        let code = header + if data[header] & 0x3 == 0x2 { 1 } else { 12 };
+        data[code] = 0xA6;
+
+        let error = PeParser::from_bytes("invalid", data.clone()).read().err().unwrap();
+        assert!(matches!(LushError::from(error), LushError::InvalidOpcode { bytes: [0xFF, 0xA6], offset: 0 }));
+
+        let image = PeParser::from_bytes("invalid", data).read_metadata_only().unwrap();
+        let token = MetadataToken::table(TableKind::MethodDef, 1);
+        assert!(matches!(image.get_method_body_by_token(token), Err(LushError::InvalidOpcode { bytes: [0xFF, 0xA6], offset: 0 })));
+        assert!(image.get_method_body(1).is_none());
+        let main = image.get_method_def(1).unwrap();
+        assert!(matches!(image.try_get_method_body(main), Err(LushError::InvalidOpcode { bytes: [0xFF, 0xA6], offset: 0 })));
+    }
+
+    #[test]
+    fn stack_deltas() {
+        assert_eq!(StackBehaviour::PopRefPopIPopI8.pop_count(), Some(3));
+        assert_eq!(StackBehaviour::Push1Push1.push_count(), Some(2));
+        assert_eq!(StackBehaviour::VarPop.pop_count(), None);
+
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // Main: nop, ldstr, call Console::WriteLine(string), nop, ret
+        let main = image.get_method_def(1).unwrap();
+        let body = image.get_method_body(1).unwrap();
+        let deltas: Vec<i32> = body.body.iter().map(|instruction| instruction.stack_delta(&image, main).unwrap()).collect();
+        assert_eq!(deltas, [0, 1, -1, 0, 0]);
+
+        // .ctor: ldarg.0, call instance Object::.ctor(), ret
+        let ctor = image.get_method_def(2).unwrap();
+        let body = image.get_method_body(2).unwrap();
+        let deltas: Vec<i32> = body.body.iter().map(|instruction| instruction.stack_delta(&image, ctor).unwrap()).collect();
+        assert_eq!(deltas, [1, -1, 0]);
+    }
+
+    #[test]
+    fn prefixed_instructions() {
+        let constrained = OpCode::parse(Code::Constrained, &mut &[0x01, 0x00, 0x00, 0x01][..]).unwrap();
+        assert_eq!(constrained.code().metadata().name, "constrained.");
+        assert_eq!(constrained.encoded_size(), 6);
+        assert_eq!(constrained.operand(), Operand::Token(MetadataToken::table(TableKind::TypeRef, 1)));
+        assert!(constrained.code().is_prefix());
+        assert!(!Code::Rethrow.is_prefix());
+
+        // readonly. constrained. T callvirt M; ret
+        let mut body = MethodBody::tiny(14 << 2);
+        body.body = vec![
+            Instruction { opcode: OpCode::Readonly(()), offset: 0 },
+            Instruction { opcode: constrained, offset: 2 },
+            Instruction { opcode: OpCode::Callvirt(MetadataToken::table(TableKind::MemberRef, 1)), offset: 8 },
+            Instruction { opcode: OpCode::Ret(()), offset: 13 },
+        ];
+        let prefixes: Vec<&str> = body.prefixes(8).iter().map(|prefix| prefix.opcode.code().metadata().name).collect();
+        assert_eq!(prefixes, ["readonly.", "constrained."]);
+        assert!(body.prefixes(13).is_empty());
+        assert!(body.prefixes(0).is_empty());
+        assert!(body.prefixes(5).is_empty());
+    }
+
+    #[test]
+    fn instructions_by_offset() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let main = image.get_method_body(1).unwrap();
+
+        let offsets = main.offset_map();
+        assert_eq!(offsets.len(), main.body.len());
+        for (index, instruction) in main.body.iter().enumerate() {
+            assert_eq!(offsets[&instruction.offset], index);
+            assert_eq!(main.instruction_at(instruction.offset).unwrap().offset, instruction.offset);
+        }
+
+        // The operand bytes of an instruction are not instruction boundaries.
+        let wide = main.body.iter().find(|instruction| instruction.opcode.encoded_size() > 1).unwrap();
+        assert!(main.instruction_at(wide.offset + 1).is_none());
+        assert!(main.instruction_at(main.code_size).is_none());
+    }
+
+    #[test]
+    fn disassemble_hello_world() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let main = image.get_method_body(1).unwrap().disassemble(&image);
+        let lines: Vec<&str> = main.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], "IL_0001: ldstr \"Hello World!\"");
+        assert_eq!(lines[2], "IL_0006: call System.Console::WriteLine");
+        assert_eq!(lines[4], "IL_000c: ret");
+
+        let ctor = image.get_method_body(2).unwrap().disassemble(&image);
+        assert_eq!(ctor, "IL_0000: ldarg.0\nIL_0001: call System.Object::.ctor\nIL_0006: ret\n");
+
+        // MethodDef and Field tokens are qualified with the type whose run owns them, every field of
+        // members_image being named HelloWorld.
+        let image = members_image();
+        let mut body = MethodBody::tiny(10 << 2);
+        body.body = vec![
+            Instruction { opcode: OpCode::parse(Code::Call, &mut &[0x02, 0x00, 0x00, 0x06][..]).unwrap(), offset: 0 },
+            Instruction { opcode: OpCode::parse(Code::Ldsfld, &mut &[0x03, 0x00, 0x00, 0x04][..]).unwrap(), offset: 5 },
+        ];
+        assert_eq!(body.disassemble(&image), "IL_0000: call HelloWorld::.ctor\nIL_0005: ldsfld HelloWorld::HelloWorld\n");
+    }
+
+    #[test]
+    fn unmapped_rva_is_an_error() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let error = image.buffer.read_method_body(0xFFFF_0000).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+        // A truncated image is reported as an error rather than a panic.
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        assert!(PeParser::from_bytes("truncated", data[..0x200].to_vec()).read().is_err());
+
+        // A code size of 8 ends in the middle of the call at IL_0006 of Main.
+        let main = image.get_method_def(1).unwrap();
+        let header = image.rva_to_offset(main.rva).unwrap() as usize;
+        data[header] = 8 << 2 | 0x2;
+        let image = PeParser::from_bytes("overrun", data).read_metadata_only().unwrap();
+        let error = image.buffer.read_method_body(main.rva).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn structural_errors_are_reported() {
+        assert!(matches!(TableKind::from_bitmask(1 << 0x3F), Err(LushError::InvalidTableKind(0x3F))));
+        assert!(matches!(
+            CodedIndexTag::TypeDefOrRef.get_table_kind(3),
+            Err(LushError::InvalidCodedIndexTag { tag: CodedIndexTag::TypeDefOrRef, value: 3 })
+        ));
+
+        let eof = std::io::Error::from(std::io::ErrorKind::UnexpectedEof);
+        assert!(matches!(LushError::from(eof), LushError::UnexpectedEof));
+    }
+
+    #[test]
+    fn resolve_coded_indices() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // The entry point is the first method of the first type after <Module>.
+        let declaring_type = match image.resolve(CodedIndex::from(TableKind::TypeDef, 2)) {
+            Some(Row::TypeDef(type_def)) => type_def,
+            row => panic!("Expected a TypeDef, found {:?}", row),
+        };
+        assert_eq!(declaring_type.method_list.index, 1);
+
+        match image.resolve(declaring_type.extends) {
+            Some(Row::TypeRef(type_ref)) => assert_eq!(image.get_string(type_ref.type_name), "Object"),
+            row => panic!("Expected a TypeRef, found {:?}", row),
+        }
+
+        assert!(image.resolve(CodedIndex::from(TableKind::TypeDef, 0)).is_none());
+        assert!(image.resolve(CodedIndex::from(TableKind::TypeDef, 100)).is_none());
+    }
+
+    #[test]
+    fn strings_at_interior_offsets() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let main = image.get_method_def(1).unwrap();
+        assert_eq!(image.get_string(main.name), "Main");
+        assert_eq!(image.get_string(StringIndex(main.name.0 + 1)), "ain");
+        assert_eq!(image.get_string(StringIndex(0)), "");
+        assert_eq!(image.get_string(StringIndex(u32::MAX)), "");
+    }
+
+    #[test]
+    fn members_of_types() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let module = image.get_type_def(1).unwrap();
+        assert!(image.methods_of(module).is_empty());
+        assert!(image.fields_of(module).is_empty());
+
+        // The last type's run extends to the end of the MethodDef table, and the image has no fields.
+        let program = image.get_type_def(2).unwrap();
+        assert!(image.get_type_def(3).is_none());
+        let methods: Vec<_> = image.methods_of(program).iter().map(|method| image.get_string(method.name)).collect();
+        assert_eq!(methods, vec!["Main", ".ctor"]);
+        assert!(image.fields_of(program).is_empty());
+
+        // Neither type is nested.
+        assert!(image.enclosing_type(program).is_none());
+        assert!(image.nested_types(module).is_empty());
+    }
+
+    #[test]
+    fn nested_types_are_grouped_by_enclosing_type() {
+        let data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let image = PeParser::from_bytes("HelloWorld.exe", data.clone()).read().unwrap();
+
+        // Copy HelloWorld into TypeDef rows 3 and 4, with empty method runs, and nest both in it.
+        let size = TableKind::TypeDef.row_size(image.streams.metadata.context());
+        let offset = row_offset(&image, TableKind::TypeDef, 1);
+        let rows = data[offset..offset + 2 * size].to_vec();
+        let mut copy = rows[size..].to_vec();
+        copy[size - 2..].copy_from_slice(&3u16.to_le_bytes());
+        let nested = |nested: u16, enclosing: u16| [nested.to_le_bytes(), enclosing.to_le_bytes()].concat();
+
+        let data = add_tables(&image, data, vec![
+            (TableKind::TypeDef, vec![rows[..size].to_vec(), rows[size..].to_vec(), copy.clone(), copy]),
+            (TableKind::NestedClass, vec![nested(3, 2), nested(4, 2)]),
+        ]);
+        let image = PeParser::from_bytes("HelloWorld.exe", data).read().unwrap();
+
+        let program = image.get_type_def(2).unwrap();
+        let indices: Vec<u32> = image.nested_types(program).iter().map(|nested| nested.index).collect();
+        assert_eq!(indices, vec![3, 4]);
+        for rid in [3, 4] {
+            let nested = image.get_type_def(rid).unwrap();
+            assert_eq!(image.enclosing_type(nested).unwrap().index, 2);
+            assert!(image.nested_types(nested).is_empty());
+        }
+        assert!(image.enclosing_type(program).is_none());
+        assert!(image.nested_types(image.get_type_def(1).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn find_types_and_methods_by_name() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        assert_eq!(image.find_type_by_name("", "<Module>").unwrap().index, 1);
+        let program = image.find_type_by_name("", "HelloWorld").unwrap();
+        assert_eq!(program.index, 2);
+        assert!(image.find_type_by_name("System", "HelloWorld").is_none());
+
+        assert_eq!(image.find_method_in_type(program, ".ctor").unwrap().index, 2);
+        assert!(image.find_method_in_type(program, "Missing").is_none());
+    }
+
+    #[test]
+    fn params_of_methods() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // Neither method has parameters, so both runs are empty, including the last one which would
+        // otherwise extend to the end of the (empty) Param table.
+        assert!(image.table::<Param>().is_empty());
+        for method in image.methods_of(image.get_type_def(2).unwrap()) {
+            assert_eq!(method.param_list.index, 1);
+            assert!(image.params_of(method).is_empty());
+        }
+    }
+
+    #[test]
+    fn blobs_are_read_from_the_raw_heap() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        assert_eq!(image.get_blob(BlobIndex(0)), Some(&[][..]));
+
+        // static void Main(): DEFAULT, no parameters, returns VOID.
+        let main = image.get_method_def(1).unwrap();
+        assert_eq!(image.get_blob(main.signature), Some(&[0x00, 0x00, 0x01][..]));
+        assert_eq!(image.get_blob(BlobIndex(u32::MAX)), None);
+    }
+
+    #[test]
+    fn user_string_literal() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let main = image.get_method_body(1).unwrap();
+        let token = match main.body[1].opcode.operand() {
+            Operand::Token(token) => token,
+            operand => panic!("Expected a token, found {:?}", operand),
+        };
+        assert_eq!(image.get_user_string(token).as_deref(), Some("Hello World!"));
+        assert_eq!(image.get_user_string(MetadataToken::from_raw(0x06000001)), None);
+    }
+
+    #[test]
+    fn parse_from_reader() {
+        let mut file = std::fs::File::open("tests/HelloWorld.exe").unwrap();
+        let image = PeParser::from_reader("HelloWorld.exe", &mut file)
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        assert_eq!(image.filename, "HelloWorld.exe");
+        assert!(image.is_executable());
+    }
+
+    #[test]
+    fn assembly_os_and_processor_rows() {
+        let row_count = std::collections::HashMap::from([(TableKind::AssemblyRef, 1), (TableKind::AssemblyRefOS, 1)]);
+        let context = TableDecodeContext::new(row_count, HeapSizes::from(0));
+
+        let mut buffer = std::io::Cursor::new(vec![1, 0, 0, 0, 5, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0xFF]);
+        let row = Row::read(&mut buffer, TableKind::AssemblyRefOS, &context).unwrap();
+        match row {
+            Row::AssemblyRefOS(os) => {
+                assert_eq!((os.os_platform_id, os.os_major, os.os_minor), (1, 5, 1));
+                assert_eq!(os.assembly_ref.index, 1);
+            }
+            row => panic!("Expected an AssemblyRefOS row, found {:?}", row),
+        }
+        assert_eq!(buffer.position(), 14);
+    }
+
+    #[test]
+    fn custom_attribute_values() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let assembly = CodedIndex::from(TableKind::Assembly, 1);
+        let values: Vec<CustomAttributeValue> = image.custom_attributes(assembly)
+            .into_iter()
+            .map(|row| image.decode_custom_attribute(row).unwrap())
+            .collect();
+
+        // [assembly: CompilationRelaxations(8)]
+        assert_eq!(values[0].fixed_args, vec![AttributeArgument::I4(8)]);
+        assert!(values[0].named_args.is_empty());
+
+        // [assembly: RuntimeCompatibility(WrapNonExceptionThrows = true)]
+        assert!(values[1].fixed_args.is_empty());
+        assert_eq!(values[1].named_args, vec![NamedArgument {
+            kind: NamedArgumentKind::Property,
+            name: "WrapNonExceptionThrows".to_string(),
+            value: AttributeArgument::Boolean(true),
+        }]);
+
+        assert!(image.custom_attributes(CodedIndex::from(TableKind::TypeDef, 2)).is_empty());
+    }
+
+    #[test]
+    fn enum_attribute_arguments() {
+        let data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let image = PeParser::from_bytes("HelloWorld.exe", data.clone()).read().unwrap();
+        let root = image.rva_to_offset(image.cli_header.meta_data.rva).unwrap() as usize;
+        let heap = |name: &str| root + image.metadata_header.stream_headers.iter().find(|header| header.name == name).unwrap().offset as usize;
+
+        // Turn HelloWorld into an enum whose value__ field has the signature `value__`, by reusing the
+        // "WriteLine" string and the blob of the Main signature.
+        let name = image.get_member_ref(3).unwrap().name.0 as usize;
+        let signature = 10;
+        let enum_image = |value_type: &[u8]| {
+            let mut data = data.clone();
+            data[heap("#Strings") + name..][..8].copy_from_slice(b"value__\0");
+            data[heap("#Blob") + signature + 1..][..value_type.len()].copy_from_slice(value_type);
+
+            // [assembly: RuntimeCompatibility(X = (HelloWorld)7)], padded to the 30 bytes of the blob.
+            let mut value = [&[0x01, 0x00, 0x01, 0x00, 0x54, 0x55, 0x0A][..], b"HelloWorld", &[0x01, b'X', 0x07]].concat();
+            value.resize(30, 0);
+            let blob = image.table::<CustomAttribute>().get(2).unwrap().value.0 as usize;
+            data[heap("#Blob") + blob + 1..][..30].copy_from_slice(&value);
+
+            let field = [1u16.to_le_bytes(), (name as u16).to_le_bytes(), (signature as u16).to_le_bytes()].concat();
+            PeParser::from_bytes("enum", add_tables(&image, data, vec![(TableKind::Field, vec![field])])).read().unwrap()
+        };
+
+        // FIELD uint8 makes the enum one byte wide.
+        let image_u1 = enum_image(&[0x06, 0x05]);
+        let value = image_u1.decode_custom_attribute(image_u1.table::<CustomAttribute>().get(2).unwrap()).unwrap();
+        assert_eq!(value.named_args[0].value, AttributeArgument::Enum {
+            type_name: "HelloWorld".to_string(),
+            value: Box::new(AttributeArgument::U1(7)),
+        });
+
+        // FIELD VALUETYPE HelloWorld is an error instead of resolving HelloWorld again and again.
+        let image_self = enum_image(&[0x06, 0x11, 0x08]);
+        let error = image_self.decode_custom_attribute(image_self.table::<CustomAttribute>().get(2).unwrap()).unwrap_err();
+        assert!(matches!(error, LushError::InvalidBlob(_)));
+    }
+
+    #[test]
+    fn table_sizes_fill_the_tables_stream() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let metadata = &image.streams.metadata;
+        let context = metadata.context();
+
+        let stream = image.metadata_header.stream_headers.iter().find(|header| header.name == "#~").unwrap();
+        let header_size = 24 + 4 * metadata.rows.len();
+        let tables_size = context.table_offset(TableKind::GenericParamConstraint)
+            + context.get_row_count(TableKind::GenericParamConstraint) as usize * TableKind::GenericParamConstraint.row_size(context);
+        // The stream is terminated by a zero byte and padded to a 4-byte boundary.
+        assert_eq!((header_size + tables_size + 1).next_multiple_of(4), stream.size as usize);
+        assert_eq!(TableKind::Module.row_size(context), 10);
+        assert_eq!(context.table_offset(TableKind::Module), 0);
+        assert_eq!(context.table_offset(TableKind::TypeRef), 10);
+    }
+
+    #[test]
+    fn uncompressed_stream_with_pointer_table() {
+        let data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let image = PeParser::from_bytes("HelloWorld.exe", data.clone()).read().unwrap();
+        let metadata = &image.streams.metadata;
+        let tables = row_offset(&image, TableKind::Module, 1);
+        let start = tables - 24 - 4 * metadata.rows.len();
+        let stream = image.metadata_header.stream_headers.iter().find(|header| header.name == "#~").unwrap();
+
+        // Rebuild the tables stream as "#-" with a MethodPtr table listing the two methods in reverse.
+        let mut uncompressed = data[start..start + 8].to_vec();
+        uncompressed.extend((metadata.valid | 1 << 5).to_le_bytes());
+        uncompressed.extend(metadata.sorted.to_le_bytes());
+        let before = metadata.present_tables().iter().filter(|kind| u8::from(**kind) < 5).count();
+        for (i, count) in metadata.rows.iter().enumerate() {
+            if i == before {
+                uncompressed.extend(2u32.to_le_bytes());
+            }
+            uncompressed.extend(count.to_le_bytes());
+        }
+        let method_defs = tables + metadata.context().table_offset(TableKind::MethodDef);
+        uncompressed.extend(&data[tables..method_defs]);
+        uncompressed.extend([0x02, 0x00, 0x01, 0x00]);
+        uncompressed.extend(&data[method_defs..start + stream.size as usize]);
+
+        let data = replace_tables_stream(&image, data, "#-", uncompressed);
+        let image = PeParser::from_bytes("HelloWorld.exe", data).read().unwrap();
+        assert!(image.warnings().is_empty());
+        assert_eq!(image.method_ptrs().map(|row| row.method.index).collect::<Vec<_>>(), [2, 1]);
+        let program = image.get_type_def(2).unwrap();
+        let methods: Vec<_> = image.methods_of(program).iter().map(|method| image.get_string(method.name)).collect();
+        assert_eq!(methods, [".ctor", "Main"]);
+        assert_eq!(image.get_method_body(1).unwrap().body.len(), 5);
+    }
+
+    #[test]
+    fn extra_data_follows_row_counts() {
+        let data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let image = PeParser::from_bytes("HelloWorld.exe", data.clone()).read().unwrap();
+        assert_eq!(image.streams.metadata.extra_data, None);
+
+        let tables = row_offset(&image, TableKind::Module, 1);
+        let stream = image.metadata_header.stream_headers.iter().find(|header| header.name == "#~").unwrap();
+        let start = tables - 24 - 4 * image.streams.metadata.rows.len();
+
+        let mut uncompressed = data[start..tables].to_vec();
+        uncompressed[6] |= 0x40;
+        uncompressed.extend(0xDEADBEEFu32.to_le_bytes());
+        uncompressed.extend(&data[tables..start + stream.size as usize]);
+
+        let data = replace_tables_stream(&image, data, "#-", uncompressed);
+        let image = PeParser::from_bytes("HelloWorld.exe", data).read().unwrap();
+        let metadata = &image.streams.metadata;
+        assert!(metadata.heap_sizes().has_extra_data());
+        assert!(!metadata.heap_sizes().has_deletes());
+        assert_eq!(metadata.extra_data, Some(0xDEADBEEF));
+        assert_eq!(image.get_string(image.get_type_def(2).unwrap().type_name), "HelloWorld");
+        assert_eq!(image.get_method_body(1).unwrap().body.len(), 5);
+    }
+
+    #[test]
+    fn sorted_tables_are_verified() {
+        assert_eq!(CodedIndex::from(TableKind::TypeDef, 2).encode(CodedIndexTag::HasCustomAttribute), Some(2 << 5 | 3));
+        assert_eq!(CodedIndex::from(TableKind::TypeDef, 2).encode(CodedIndexTag::HasConstant), None);
+
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let image = PeParser::from_bytes("HelloWorld.exe", data.clone()).read().unwrap();
+        let metadata = &image.streams.metadata;
+        assert!(metadata.is_sorted(TableKind::CustomAttribute));
+        assert!(metadata.verify_sorted().is_empty());
+        assert!(image.is_ordered(TableKind::CustomAttribute));
+        let assembly = CodedIndex::from(TableKind::Assembly, 1);
+        let attributes = image.custom_attributes(assembly).len();
+        assert_eq!(attributes, image.table::<CustomAttribute>().len());
+
+        // Move the first custom attribute, which belongs to the assembly like the others, to the HelloWorld type.
+        assert!(image.table::<CustomAttribute>().len() > 1);
+        assert!(image.all_custom_attributes().all(|row| row.parent.table == TableKind::Assembly));
+        assert_eq!(metadata.coded_index_size(CodedIndexTag::HasCustomAttribute), 2);
+        let parent = CodedIndex::from(TableKind::TypeDef, 2).encode(CodedIndexTag::HasCustomAttribute).unwrap() as u16;
+        let first = row_offset(&image, TableKind::CustomAttribute, 1);
+        data[first..first + 2].copy_from_slice(&parent.to_le_bytes());
+
+        let image = PeParser::from_bytes("HelloWorld.exe", data).read().unwrap();
+        assert_eq!(image.streams.metadata.verify_sorted(), [TableKind::CustomAttribute]);
+        assert!(!image.is_ordered(TableKind::CustomAttribute));
+        assert_eq!(image.custom_attributes(assembly).len(), attributes - 1);
+        assert_eq!(image.custom_attributes(CodedIndex::from(TableKind::TypeDef, 2)).len(), 1);
+        assert!(image.warnings().contains(&ParseWarning::SpecViolation(
+            "CustomAttribute table is flagged as sorted, but isn't ordered by its primary key".to_string()
+        )));
+    }
+
+    #[test]
+    fn binary_search_by_key() {
+        let rows: Vec<Row> = [1, 2, 2, 2, 5].iter().enumerate().map(|(i, rid)| Row::FieldRVA(FieldRVA {
+            index: i as u32 + 1,
+            rva: 0,
+            field: CodedIndex::from(TableKind::Field, *rid),
+        })).collect();
+
+        let indices = |target| find_range_by_key(&rows, Row::sort_key, target).iter().map(Row::index).collect::<Vec<_>>();
+        assert_eq!(indices(2), [2, 3, 4]);
+        assert_eq!(indices(5), [5]);
+        assert!(indices(3).is_empty());
+        assert!(indices(6).is_empty());
+    }
+
+    #[test]
+    fn malformed_rows_are_reported_by_read() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // Point the Type column of the first CustomAttribute row at the unused CustomAttributeType tag 0.
+        let column = row_offset(&image, TableKind::CustomAttribute, 1)
+            + image.streams.metadata.coded_index_size(CodedIndexTag::HasCustomAttribute) as usize;
+
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        data[column..column + 2].copy_from_slice(&[0, 0]);
+
+        let error = LushError::from(PeParser::from_bytes("corrupted", data.clone()).read().err().unwrap());
+        assert!(matches!(error, LushError::InvalidCodedIndexTag { tag: CodedIndexTag::CustomAttributeType, value: 0 }));
+        assert!(PeParser::from_bytes("corrupted", data.clone()).read_metadata_only().is_err());
+        assert!(PeParser::from_bytes("corrupted", data.clone()).read_with(ParseOptions::default()).is_err());
+
+        let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+        assert!(PeParser::from_bytes("corrupted", data).read_with(strict).is_err());
+
+        // A Module row count past the end of the stream fails before anything is allocated for it.
+        let count = row_offset(&image, TableKind::Module, 1) - 4 * image.streams.metadata.rows.len();
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        data[count..count + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let error = LushError::from(PeParser::from_bytes("corrupted", data).read().err().unwrap());
+        assert!(matches!(error, LushError::UnexpectedEof));
+    }
+
+    #[test]
+    fn corrupted_tables_never_panic() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let stream = image.metadata_header.stream_headers.iter().find(|header| header.name == "#~").unwrap();
+        let start = row_offset(&image, TableKind::Module, 1);
+        let end = image.rva_to_offset(image.cli_header.meta_data.rva).unwrap() as usize + (stream.offset + stream.size) as usize;
+        let data = std::fs::read("tests/HelloWorld.exe").unwrap();
+
+        // Every byte of every row set to a few values either fails to read or reads an image whose
+        // accessors don't panic.
+        for offset in start..end {
+            for value in [0x00, 0x07, 0xFF] {
+                let mut data = data.clone();
+                data[offset] = value;
+
+                let result = std::panic::catch_unwind(|| {
+                    if let Ok(image) = PeParser::from_bytes("corrupted", data).read_metadata_only() {
+                        image.dump_tables();
+                        image.diff(&image);
+                        image.custom_attributes(CodedIndex::from(TableKind::Assembly, 1));
+                        #[cfg(feature = "serde")]
+                        image.to_json();
+                    }
+                });
+                assert!(result.is_ok(), "setting byte 0x{:X} to 0x{:02X} panicked", offset, value);
+            }
+        }
+    }
+
+    #[test]
+    fn row_tokens_round_trip() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let main = image.get_method_def(1).unwrap();
+        assert_eq!(main.token(), image.cli_header.entry_point_token);
+        assert_eq!(main.token().to_raw(), 0x06000001);
+
+        let row = image.resolve(CodedIndex::from(TableKind::TypeDef, 2)).unwrap();
+        assert_eq!(row.kind(), TableKind::TypeDef);
+        assert_eq!(MetadataToken::from_row(row), MetadataToken::table(TableKind::TypeDef, 2));
+    }
+
+    #[test]
+    fn type_full_names() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        assert_eq!(image.type_full_name(CodedIndex::from(TableKind::TypeDef, 2)).as_deref(), Some("HelloWorld"));
+        assert_eq!(image.type_full_name(CodedIndex::from(TableKind::TypeRef, 4)).as_deref(), Some("[mscorlib]System.Console"));
+        assert_eq!(image.type_full_name(CodedIndex::from(TableKind::MethodDef, 1)), None);
+
+        // Resolve System.Console in the scope of System.Object, as if it were nested in it.
+        let row = row_offset(&image, TableKind::TypeRef, 4);
+
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        data[row..row + 2].copy_from_slice(&[1 << 2 | 3, 0]);
+        let image = PeParser::from_bytes("nested", data).read().unwrap();
+
+        assert_eq!(
+            image.type_full_name(CodedIndex::from(TableKind::TypeRef, 4)).as_deref(),
+            Some("[mscorlib]System.Object+System.Console")
+        );
+    }
+
+    #[test]
+    fn field_signatures() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let context = image.streams.metadata.context();
+
+        // FIELD CMOD_REQD TypeRef[1] I4, e.g. a volatile int.
+        let field = FieldSignature::parse(&[0x06, 0x1f, 0x05, 0x08], context).unwrap();
+        assert_eq!(field.custom_modifiers, vec![CustomMod { required: true, type_ref: CodedIndex::from(TableKind::TypeRef, 1) }]);
+        assert_eq!(field.field_type, SigType::I4);
+
+        let method = FieldSignature::parse(&[0x00, 0x00, 0x01], context).unwrap_err();
+        assert_eq!(method.kind(), std::io::ErrorKind::InvalidData);
+
+        // void modreq(TypeRef[1]) (int32 modopt(TypeRef[1])*)
+        let object = CodedIndex::from(TableKind::TypeRef, 1);
+        let method = MethodSignature::parse(&[0x00, 0x01, 0x1f, 0x05, 0x01, 0x0f, 0x20, 0x05, 0x08], context).unwrap();
+        assert_eq!(method.return_type, SigType::Modified {
+            modifiers: vec![CustomMod { required: true, type_ref: object }],
+            inner: Box::new(SigType::Void),
+        });
+        assert_eq!(method.params, vec![SigType::Ptr(Box::new(SigType::Modified {
+            modifiers: vec![CustomMod { required: false, type_ref: object }],
+            inner: Box::new(SigType::I4),
+        }))]);
+    }
+
+    #[test]
+    fn declarative_security() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let mut blob = vec![b'.', 0x01, 0x04];
+        blob.extend(b"Perm");
+        let mut arguments = vec![0x01, 0x54, 0x02, 0x0D];
+        arguments.extend(b"UnmanagedCode");
+        arguments.push(0x01);
+        blob.push(arguments.len() as u8);
+        blob.extend(arguments);
+
+        assert_eq!(image.read_permission_set(&blob).unwrap(), PermissionSet::Attributes(vec![SecurityAttribute {
+            type_name: "Perm".to_string(),
+            named_args: vec![NamedArgument {
+                kind: NamedArgumentKind::Property,
+                name: "UnmanagedCode".to_string(),
+                value: AttributeArgument::Boolean(true),
+            }],
+        }]));
+
+        let xml: Vec<u8> = "<PermissionSet/>".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(image.read_permission_set(&xml).unwrap(), PermissionSet::Xml("<PermissionSet/>".to_string()));
+        assert!(image.read_permission_set(&[0x00]).is_err());
+    }
+
+    #[test]
+    fn constant_values() {
+        assert_eq!(ConstantValue::parse(0x02, &[0x01]).unwrap(), ConstantValue::Boolean(true));
+        assert_eq!(ConstantValue::parse(0x08, &[0xFE, 0xFF, 0xFF, 0xFF]).unwrap(), ConstantValue::I4(-2));
+        assert_eq!(ConstantValue::parse(0x0d, &1.5f64.to_le_bytes()).unwrap(), ConstantValue::R8(1.5));
+        assert_eq!(ConstantValue::parse(0x0e, &[b'h', 0, b'i', 0]).unwrap(), ConstantValue::String("hi".to_string()));
+        assert_eq!(ConstantValue::parse(0x0e, &[]).unwrap(), ConstantValue::String(String::new()));
+        assert_eq!(ConstantValue::parse(0x12, &[0, 0, 0, 0]).unwrap(), ConstantValue::NullRef);
+        assert!(ConstantValue::parse(0x12, &[1, 0, 0, 0]).is_err());
+        assert!(ConstantValue::parse(0x08, &[0x01]).is_err());
+
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        assert_eq!(image.constant_of(CodedIndex::from(TableKind::Field, 1)).unwrap(), None);
+        assert_eq!(image.constant_of(CodedIndex::from(TableKind::TypeDef, 1)).unwrap(), None);
+    }
+
+    #[test]
+    fn property_and_event_accessors() {
+        let image = members_image();
+        let index = |method: Option<&MethodDef>| method.map(|method| method.index);
+        let indices = |methods: &[&MethodDef]| methods.iter().map(|method| method.index).collect::<Vec<_>>();
+        let (main, ctor) = (Some(1), Some(2));
+
+        let property = image.property_accessors(image.properties().next().unwrap());
+        assert_eq!((index(property.getter), index(property.setter)), (ctor, main));
+        assert_eq!(indices(&property.others), [1]);
+
+        let property = image.property_accessors(image.properties().nth(1).unwrap());
+        assert_eq!((index(property.getter), index(property.setter)), (main, None));
+        assert!(property.others.is_empty());
+
+        let event = image.event_accessors(image.events().next().unwrap());
+        assert_eq!((index(event.add_on), index(event.remove_on), index(event.fire)), (ctor, main, main));
+        assert_eq!(indices(&event.others), [2]);
+
+        let event = image.event_accessors(image.events().nth(1).unwrap());
+        assert_eq!((index(event.add_on), index(event.remove_on), index(event.fire)), (main, None, None));
+        assert!(event.others.is_empty());
+    }
+
+    #[test]
+    fn pinvoke_info() {
+        let flags = PInvokeAttributes::new(0x0204);
+        assert_eq!(flags.char_set(), CharSet::Unicode);
+        assert_eq!(flags.call_conv(), CallConv::Cdecl);
+        assert_eq!(PInvokeAttributes::new(0x0000).call_conv(), CallConv::Invalid);
+
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let main = image.get_method_def(1).unwrap();
+        assert!(!main.is_pinvoke());
+        assert_eq!(image.pinvoke_info(main), None);
+    }
+
+    #[test]
+    fn type_layout_and_generics() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let hello_world = image.get_type_def(2).unwrap();
+        assert_eq!(hello_world.layout(), Some(TypeLayout::Auto));
+        assert!(image.class_layout(hello_world).is_none());
+        assert!(image.fields_of(hello_world).iter().all(|field| image.field_offset(field).is_none()));
+        assert!(!image.spec_violations().iter().any(|violation| violation.contains("Layout")));
+        assert!(image.interfaces_of(hello_world).is_empty());
+        assert!(image.generic_params_of(CodedIndex::from(TableKind::TypeDef, 2)).is_empty());
+        assert!(image.generic_params_of(CodedIndex::from(TableKind::Field, 1)).is_empty());
+
+        // Neither HelloWorld nor Main is generic, so their parameters render like ildasm's.
+        let main = CodedIndex::from(TableKind::MethodDef, 1);
+        assert_eq!(image.resolve_var_name(main, false, 0), None);
+        let list = SigType::GenericInst {
+            base: Box::new(SigType::Class(CodedIndex::from(TableKind::TypeDef, 2))),
+            args: vec![SigType::Var(0), SigType::SzArray(Box::new(SigType::MVar(1)))],
+        };
+        assert_eq!(image.sig_type_name(&list, Some(main)), "HelloWorld<!0,!!1[]>");
+    }
+
+    #[test]
+    fn format_types_like_csharp() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let hello_world = || Box::new(SigType::Class(CodedIndex::from(TableKind::TypeDef, 2)));
+        let object = CodedIndex::from(TableKind::TypeRef, 1);
+
+        assert_eq!(image.format_type(&SigType::String), "string");
+        assert_eq!(image.format_type(&SigType::Class(object)), "object");
+        assert_eq!(image.format_type(&SigType::SzArray(Box::new(SigType::I4))), "int[]");
+        assert_eq!(image.format_type(&SigType::Array { element: Box::new(SigType::R8), rank: 2, sizes: vec![], lo_bounds: vec![] }), "double[,]");
+        assert_eq!(image.format_type(&SigType::ByRef(Box::new(SigType::U1))), "ref byte");
+        assert_eq!(image.format_type(&SigType::Ptr(Box::new(SigType::Ptr(Box::new(SigType::Char))))), "char**");
+        assert_eq!(image.format_type(&SigType::GenericInst {
+            base: hello_world(),
+            args: vec![SigType::String, SigType::GenericInst { base: hello_world(), args: vec![SigType::MVar(0)] }],
+        }), "HelloWorld<string, HelloWorld<!!0>>");
+        assert_eq!(image.format_type(&SigType::Modified {
+            modifiers: vec![CustomMod { required: true, type_ref: object }],
+            inner: Box::new(SigType::ByRef(Box::new(SigType::I8))),
+        }), "ref long");
+        assert_eq!(image.format_type(&SigType::Class(CodedIndex::from(TableKind::TypeDef, 9))), "TypeDef[9]");
+    }
+
+    #[test]
+    fn assembly_names() {
+        // The ECMA standard public key, which mscorlib references are signed with.
+        let mut ecma_key = [0; 16];
+        ecma_key[8] = 0x04;
+        assert_eq!(public_key_token(&ecma_key), [0xb7, 0x7a, 0x5c, 0x56, 0x19, 0x34, 0xe0, 0x89]);
+
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        assert_eq!(image.assembly_name().unwrap(), AssemblyName {
+            name: "HelloWorld".to_string(),
+            version: (0, 0, 0, 0),
+            culture: None,
+            public_key_token: None,
+        });
+        assert_eq!(image.assembly_references(), vec![AssemblyName {
+            name: "mscorlib".to_string(),
+            version: (4, 0, 0, 0),
+            culture: None,
+            public_key_token: Some([0xb7, 0x7a, 0x5c, 0x56, 0x19, 0x34, 0xe0, 0x89]),
+        }]);
+    }
+
+    #[test]
+    fn property_signatures() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let context = image.streams.metadata.context();
+
+        // An instance indexer: string this[int32, string]
+        let indexer = PropertySignature::parse(&[0x28, 0x02, 0x0e, 0x08, 0x0e], context).unwrap();
+        assert!(indexer.has_this);
+        assert_eq!(indexer.property_type, SigType::String);
+        assert_eq!(indexer.index_params, vec![SigType::I4, SigType::String]);
+
+        // A static property: static int32 Count
+        let count = PropertySignature::parse(&[0x08, 0x00, 0x08], context).unwrap();
+        assert!(!count.has_this);
+        assert_eq!(count.property_type, SigType::I4);
+        assert!(count.index_params.is_empty());
+
+        let field = PropertySignature::parse(&[0x06, 0x08], context).unwrap_err();
+        assert_eq!(field.kind(), std::io::ErrorKind::InvalidData);
+
+        // Main's method signature, and an index outside the #Blob heap.
+        let main = image.get_method_def(1).unwrap().signature;
+        for type_ in [main, BlobIndex(u32::MAX)] {
+            let property = Property { index: 1, flags: PropertyAttributes::new(0), name: StringIndex(0), type_ };
+            assert!(matches!(image.get_property_signature(&property), Err(LushError::InvalidSignature(index)) if index == type_.0));
+        }
+    }
+
+    #[test]
+    fn sig_type_encodings() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let context = image.streams.metadata.context();
+        let object = CodedIndex::from(TableKind::TypeRef, 1);
+
+        // int32[3...,-1...] with an unspecified size for the second dimension.
+        let mut array: &[u8] = &[0x14, 0x08, 0x02, 0x01, 0x03, 0x01, 0x7f, 0xff];
+        assert_eq!(SigType::parse(&mut array, context).unwrap(), SigType::Array {
+            element: Box::new(SigType::I4),
+            rank: 2,
+            sizes: vec![3],
+            lo_bounds: vec![-1],
+        });
+        assert_eq!(array, &[0xff]);
+
+        let mut generic: &[u8] = &[0x15, 0x12, 0x05, 0x01, 0x08];
+        assert_eq!(SigType::parse(&mut generic, context).unwrap(), SigType::GenericInst {
+            base: Box::new(SigType::Class(object)),
+            args: vec![SigType::I4],
+        });
+
+        let mut function: &[u8] = &[0x1b, 0x00, 0x01, 0x01, 0x1c];
+        match SigType::parse(&mut function, context).unwrap() {
+            SigType::FnPtr(signature) => {
+                assert_eq!(signature.return_type, SigType::Void);
+                assert_eq!(signature.params, vec![SigType::Object]);
+            }
+            other => panic!("Expected a function pointer, found {:?}", other),
+        }
+
+        let instantiation = MethodSpecSignature::parse(&[0x0a, 0x02, 0x0e, 0x08], context).unwrap();
+        assert_eq!(instantiation.args, vec![SigType::String, SigType::I4]);
+        assert!(MethodSpecSignature::parse(&[0x06, 0x08], context).is_err());
+    }
+
+    #[test]
+    fn clr_entry_stub_import() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // An executable imports _CorExeMain, a library would import _CorDllMain instead.
+        assert_eq!(image.imports().unwrap(), vec![ImportedModule {
+            name: "mscoree.dll".to_string(),
+            functions: vec!["_CorExeMain".to_string()],
+        }]);
+    }
+
+    #[test]
+    fn field_rva_data() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // The fixture has no FieldRVA rows, so point one at the body of Main.
+        let rva = image.get_method_def(1).unwrap().rva;
+        let row = FieldRVA { index: 1, rva, field: CodedIndex::from(TableKind::Field, 1) };
+        assert_eq!(image.field_rva_data(&row, 4).unwrap(), image.buffer.get_rva_slice(rva, 4).unwrap());
+
+        let unmapped = FieldRVA { rva: 0xFFFF_0000, ..row };
+        assert!(image.field_rva_data(&unmapped, 4).is_err());
+    }
+
+    #[test]
+    fn embedded_manifest_resource() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // The fixture has no resources, so overwrite the body of Main with one and point the Resources
+        // directory of the CLI header at it.
+        let rva = image.get_method_def(1).unwrap().rva;
+        let body = image.rva_to_offset(rva).unwrap() as usize;
+        let cli_header = image.rva_to_offset(image.optional_header.data_directories.cli_header.rva).unwrap() as usize;
+
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        data[body..body + 7].copy_from_slice(&[3, 0, 0, 0, b'a', b'b', b'c']);
+        data[cli_header + 24..cli_header + 28].copy_from_slice(&rva.to_le_bytes());
+        data[cli_header + 28..cli_header + 32].copy_from_slice(&7u32.to_le_bytes());
+        let image = PeParser::from_bytes("resources", data).read_metadata_only().unwrap();
+
+        let embedded = ManifestResource {
+            index: 1,
+            offset: 0,
+            flags: ManifestResourceAttributes::new(0x0001),
+            name: StringIndex(0),
+            implementation: CodedIndex::from(TableKind::File, 0),
+        };
+        assert_eq!(image.manifest_resource_data(&embedded).unwrap(), Some(b"abc".to_vec()));
+
+        let external = ManifestResource { implementation: CodedIndex::from(TableKind::AssemblyRef, 1), ..embedded };
+        assert_eq!(image.manifest_resource_data(&external).unwrap(), None);
+
+        let truncated = ManifestResource { offset: 4, ..embedded };
+        assert!(image.manifest_resource_data(&truncated).is_err());
+    }
+
+    #[test]
+    fn pe_checksum() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // The compiler leaves the checksum at 0, as the spec suggests.
+        assert_eq!(image.optional_header.nt_specific_fields.file_checksum, 0);
+        assert!(image.verify_checksum());
+        let checksum = image.compute_checksum();
+
+        let offset = u32::from_le_bytes(image.buffer.bytes()[0x3c..0x40].try_into().unwrap()) as usize + 4 + 20 + 64;
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        data[offset..offset + 4].copy_from_slice(&checksum.to_le_bytes());
+        let signed = PeParser::from_bytes("signed", data.clone()).read().unwrap();
+        assert_eq!(signed.compute_checksum(), checksum);
+        assert!(signed.verify_checksum());
+
+        // Tamper with the last byte of the file.
+        *data.last_mut().unwrap() ^= 0xFF;
+        assert!(!PeParser::from_bytes("tampered", data).read().unwrap().verify_checksum());
+    }
+
+    #[test]
+    fn vtable_fixups() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        assert!(image.vtable_fixups().is_empty());
+
+        // Point the VTableFixups directory at the unused ExportAddressTableJumps and ManagedNativeHeader
+        // fields at the end of the CLI header, and store two entries there.
+        let rva = image.optional_header.data_directories.cli_header.rva;
+        let cli_header = image.rva_to_offset(rva).unwrap() as usize;
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        data[cli_header + 48..cli_header + 52].copy_from_slice(&(rva + 56).to_le_bytes());
+        data[cli_header + 52..cli_header + 56].copy_from_slice(&16u32.to_le_bytes());
+        data[cli_header + 56..cli_header + 64].copy_from_slice(&[0x00, 0x40, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00]);
+        data[cli_header + 64..cli_header + 72].copy_from_slice(&[0x08, 0x40, 0x00, 0x00, 0x02, 0x00, 0x06, 0x00]);
+
+        let image = PeParser::from_bytes("fixups", data.clone()).read().unwrap();
+        let fixups = image.vtable_fixups();
+        assert_eq!(fixups.iter().map(|fixup| (fixup.rva, fixup.count)).collect::<Vec<_>>(), [(0x4000, 1), (0x4008, 2)]);
+        assert_eq!(fixups[0].type_, VTableFixupFlags::COR_VTABLE_32BIT);
+        assert_eq!(fixups[1].type_, VTableFixupFlags::COR_VTABLE_64BIT | VTableFixupFlags::COR_VTABLE_FROM_UNMANAGED);
+
+        // A directory outside every section reads as no fixups.
+        data[cli_header + 48..cli_header + 52].copy_from_slice(&0xFFFF_0000u32.to_le_bytes());
+        let image = PeParser::from_bytes("fixups", data).read().unwrap();
+        assert!(image.vtable_fixups().is_empty());
+    }
+
+    #[test]
+    fn assembly_flags_are_four_bytes() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let assembly = row_offset(&image, TableKind::Assembly, 1) + 12;
+        let assembly_ref = row_offset(&image, TableKind::AssemblyRef, 1) + 8;
+
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        data[assembly..assembly + 4].copy_from_slice(&0x0001_0001u32.to_le_bytes());
+        data[assembly_ref..assembly_ref + 4].copy_from_slice(&0x8000_0100u32.to_le_bytes());
+
+        let image = PeParser::from_bytes("flags", data).read().unwrap();
+        let assembly = image.get_assembly().unwrap();
+        assert_eq!(u32::from(assembly.flags), 0x0001_0001);
+        assert!(assembly.flags.contains(AssemblyFlags::PUBLIC_KEY));
+        assert_eq!(image.get_string(assembly.name), "HelloWorld");
+
+        let mscorlib = image.get_assembly_ref(1).unwrap();
+        assert_eq!(u32::from(mscorlib.flags), 0x8000_0100);
+        assert!(mscorlib.flags.contains(AssemblyFlags::RETARGETABLE));
+        assert_eq!(image.get_string(mscorlib.name), "mscorlib");
+    }
+
+    #[test]
+    fn missing_streams() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let root = image.rva_to_offset(image.cli_header.meta_data.rva).unwrap() as usize;
+        let rename = |from: &[u8], to: &[u8]| {
+            let mut data = data.clone();
+            let header = data[root..].windows(from.len()).position(|name| name == from).unwrap() + root;
+            data[header..header + to.len()].copy_from_slice(to);
+            data
+        };
+
+        // Without #US, the user string heap reads as empty.
+        let image = PeParser::from_bytes("no #US", rename(b"#US\0", b"#UX\0")).read().unwrap();
+        assert_eq!(image.warnings(), [ParseWarning::MissingStream("#US"), ParseWarning::UnknownStream("#UX".to_string())]);
+        let main = image.get_method_body(1).unwrap();
+        let Operand::Token(token) = main.body[1].opcode.operand() else {
+            panic!("Expected a token");
+        };
+        assert_eq!(image.get_user_string(token), None);
+
+        // Without #~, there's nothing to read.
+        let error = LushError::from(PeParser::from_bytes("no #~", rename(b"#~\0", b"#X\0")).read().err().unwrap());
+        assert!(matches!(error, LushError::MissingStream("#~")));
+    }
+
+    #[test]
+    fn strong_name_flag_without_signature() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        assert!(!image.is_strong_named());
+        assert!(image.warnings().is_empty());
+
+        // Flag the image as signed while leaving the StrongNameSignature directory empty.
+        let cli_header = image.rva_to_offset(image.optional_header.data_directories.cli_header.rva).unwrap() as usize;
+        let flags = u32::from(image.cli_header.flags) | 0x0000_0008;
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        data[cli_header + 16..cli_header + 20].copy_from_slice(&flags.to_le_bytes());
+
+        let image = PeParser::from_bytes("unsigned", data.clone()).read().unwrap();
+        assert!(!image.is_strong_named());
+        assert!(matches!(image.warnings(), [ParseWarning::SpecViolation(_)]));
+
+        let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+        assert!(matches!(PeParser::from_bytes("unsigned", data).read_with(strict), Err(LushError::SpecViolation(_))));
+    }
+
+    #[test]
+    fn header_spec_violations() {
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        let lfanew = u32::from_le_bytes(data[0x3c..0x40].try_into().unwrap()) as usize;
+
+        // Clear IMAGE_FILE_EXECUTABLE_IMAGE and claim 15 data directories.
+        data[lfanew + 4 + 18] &= !0x02;
+        data[lfanew + 24 + 92..lfanew + 24 + 96].copy_from_slice(&15u32.to_le_bytes());
+
+        let image = PeParser::from_bytes("lint", data).read().unwrap();
+        let violations: Vec<&str> = image.warnings().iter().filter_map(|warning| match warning {
+            ParseWarning::SpecViolation(violation) => Some(violation.as_str()),
+            _ => None,
+        }).collect();
+        assert_eq!(violations, [
+            "IMAGE_FILE_EXECUTABLE_IMAGE is clear, shall be one",
+            "image has 15 data directories, shall have 16",
+        ]);
+    }
+
+    #[test]
+    fn section_names() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let text = image.section_by_name(".text").unwrap();
+        assert_eq!(text.name_str(), ".text");
+        let cli_header = image.optional_header.data_directories.cli_header.rva;
+        assert!((text.virtual_address..text.virtual_address + text.virtual_size).contains(&cli_header));
+        assert!(image.section_by_name(".missing").is_none());
+
+        // A name of exactly eight characters has no null terminator.
+        let mut raw = [0u8; 40];
+        raw[..8].copy_from_slice(b".textbss");
+        assert_eq!(SectionHeader::from(&raw).name_str(), ".textbss");
+    }
+
+    #[test]
+    fn image_layout() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        assert_eq!(image.image_base(), 0x0040_0000);
+        assert_eq!(image.section_alignment(), 0x2000);
+        assert_eq!(image.file_alignment(), 0x200);
+        assert_eq!(image.image_size(), 0x8000);
+        assert_eq!(image.header_size(), 0x200);
+
+        for section in image.sections() {
+            assert!(section.virtual_address.is_multiple_of(image.section_alignment()));
+            assert!(section.pointer_to_raw_data.is_multiple_of(image.file_alignment()));
+            assert!(section.pointer_to_raw_data >= image.header_size());
+            assert!(section.virtual_address + section.virtual_size <= image.image_size());
+        }
+    }
+
+    #[test]
+    fn offset_to_rva_round_trip() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        let cli_header = image.optional_header.data_directories.cli_header.rva;
+        for rva in [cli_header, image.cli_header.meta_data.rva, image.cli_header.meta_data.rva + 0x10] {
+            let offset = image.rva_to_offset(rva).unwrap();
+            assert_eq!(image.offset_to_rva(offset), Some(rva));
+        }
+        for section in image.sections() {
+            assert_eq!(image.offset_to_rva(section.pointer_to_raw_data as u64), Some(section.virtual_address));
+        }
+
+        // The lfanew field of the MS-DOS header, and the end of the file.
+        assert_eq!(image.offset_to_rva(0x3c), None);
+        assert_eq!(image.offset_to_rva(image.header_size() as u64 - 1), None);
+        assert_eq!(image.offset_to_rva(std::fs::metadata("tests/HelloWorld.exe").unwrap().len()), None);
+    }
+
+    #[test]
+    fn token_display_round_trip() {
+        let method = MetadataToken::table(TableKind::MethodDef, 1);
+        assert_eq!(method.to_string(), "0x06000001");
+        assert_eq!(method.to_string().parse::<MetadataToken>().unwrap(), method);
+
+        let string = MetadataToken::user_string(0x1d);
+        assert_eq!(string.to_string(), "0x7000001D");
+        assert_eq!(string.to_string().parse::<MetadataToken>().unwrap(), string);
+        assert_eq!("MethodDef:1".parse::<MetadataToken>().unwrap(), method);
+
+        // Indices past 24 bits can't be stored in a token.
+        assert_eq!(MetadataToken::try_table(TableKind::MethodDef, 1), Some(method));
+        assert_eq!(MetadataToken::try_table(TableKind::MethodDef, 0x0100_0000), None);
+        assert_eq!(MetadataToken::try_user_string(0x0100_0000), None);
+        assert!(matches!("UserString:0x1000000".parse::<MetadataToken>(), Err(LushError::InvalidToken(_))));
+        assert!(std::panic::catch_unwind(|| MetadataToken::table(TableKind::MethodDef, 0x0100_0000)).is_err());
+    }
+
+    #[test]
+    fn unknown_tables_in_token_text() {
+        // 0x2D is past the last table, 0x2C GenericParamConstraint.
+        assert!(matches!("0x2D000001".parse::<MetadataToken>(), Err(LushError::InvalidTableKind(0x2D))));
+        assert!(matches!("0xFF000001".parse::<MetadataToken>(), Err(LushError::InvalidTableKind(0xFF))));
+        assert_eq!("0x2C000001".parse::<MetadataToken>().unwrap(), MetadataToken::table(TableKind::GenericParamConstraint, 1));
+        assert!(matches!("Bogus:1".parse::<MetadataToken>(), Err(LushError::InvalidToken(token)) if token == "Bogus:1"));
+    }
+
+    #[test]
+    fn table_iterators() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        assert_eq!(image.type_defs().count(), image.table::<TypeDef>().len());
+        let names: Vec<_> = image.method_defs().map(|method_def| image.get_string(method_def.name)).collect();
+        assert!(names.iter().any(|name| name == "Main"));
+        assert_eq!(image.member_refs().map(|member_ref| member_ref.index).collect::<Vec<_>>(), (1..=image.table::<MemberRef>().len() as u32).collect::<Vec<_>>());
+
+        // Absent tables yield nothing rather than panicking.
+        assert_eq!(image.generic_params().count(), 0);
+    }
+
+    #[test]
+    fn abstract_method_has_no_body() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        // Turn the .ctor into an abstract interface-style method: RVA 0, flagged abstract and virtual.
+        let offset = row_offset(&image, TableKind::MethodDef, 2);
+        let flags = MethodAttributes::PUBLIC | MethodAttributes::VIRTUAL | MethodAttributes::ABSTRACT;
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        data[offset..offset + 4].copy_from_slice(&0u32.to_le_bytes());
+        data[offset + 6..offset + 8].copy_from_slice(&u16::from(flags).to_le_bytes());
+
+        // Eagerly loading the bodies skips the method instead of reading a header at RVA 0.
+        let image = PeParser::from_bytes("abstract", data).read().unwrap();
+        assert!(image.get_method_def(2).unwrap().flags.contains(MethodAttributes::ABSTRACT));
+        assert!(image.get_method_body(2).is_none());
+        assert!(image.get_method_body(1).is_some());
+
+        let token = MetadataToken::table(TableKind::MethodDef, 2);
+        assert!(matches!(image.get_method_body_by_token(token), Err(LushError::NoMethodBody(t)) if t == token));
+    }
+
+    #[test]
+    fn attribute_helpers() {
+        let interface = TypeAttributes::PUBLIC | TypeAttributes::INTERFACE | TypeAttributes::ABSTRACT;
+        assert!(interface.is_interface());
+        assert!(!(TypeAttributes::PUBLIC | TypeAttributes::ABSTRACT).is_interface());
+        assert_eq!(interface.visibility(), TypeVisibility::Public);
+
+        // The visibility is a 3 bit value, not a set of flags.
+        let visibilities = [
+            TypeVisibility::NotPublic,
+            TypeVisibility::Public,
+            TypeVisibility::NestedPublic,
+            TypeVisibility::NestedPrivate,
+            TypeVisibility::NestedFamily,
+            TypeVisibility::NestedAssembly,
+            TypeVisibility::NestedFamAndAssem,
+            TypeVisibility::NestedFamOrAssem,
+        ];
+        for (value, visibility) in visibilities.into_iter().enumerate() {
+            assert_eq!(TypeAttributes::new(value as u32 | 0x0100).visibility(), visibility);
+        }
+        let nested = TypeAttributes::NESTED_FAM_OR_ASSEM | TypeAttributes::SEALED;
+        assert_eq!(nested.visibility(), TypeVisibility::NestedFamOrAssem);
+
+        let accesses = [
+            (MethodAttributes::COMPILER_CONTROLLED, MemberAccess::CompilerControlled),
+            (MethodAttributes::PRIVATE, MemberAccess::Private),
+            (MethodAttributes::FAM_AND_ASSEM, MemberAccess::FamAndAssem),
+            (MethodAttributes::ASSEM, MemberAccess::Assembly),
+            (MethodAttributes::FAMILY, MemberAccess::Family),
+            (MethodAttributes::FAM_OR_ASSEM, MemberAccess::FamOrAssem),
+            (MethodAttributes::PUBLIC, MemberAccess::Public),
+            (MethodAttributes::new(0x0007), MemberAccess::Invalid),
+        ];
+        for (flags, access) in accesses {
+            assert_eq!((flags | MethodAttributes::STATIC | MethodAttributes::VIRTUAL).access(), access);
+        }
+    }
+
+    #[test]
+    fn flag_names() {
+        let method = MethodAttributes::PUBLIC | MethodAttributes::STATIC | MethodAttributes::HIDE_BY_SIG | MethodAttributes::NEW_SLOT;
+        assert_eq!(method.flag_names(), ["PUBLIC", "STATIC", "HIDE_BY_SIG", "NEW_SLOT"]);
+        assert_eq!(MethodAttributes::new(0).flag_names(), ["COMPILER_CONTROLLED", "REUSE_SLOT"]);
+
+        let type_def = TypeAttributes::PUBLIC | TypeAttributes::SEQUENTIAL_LAYOUT | TypeAttributes::SEALED | TypeAttributes::BEFORE_FIELD_INIT;
+        assert_eq!(type_def.flag_names(), ["PUBLIC", "SEQUENTIAL_LAYOUT", "CLASS", "SEALED", "ANSI_CLASS", "BEFORE_FIELD_INIT"]);
+        let nested = TypeAttributes::NESTED_FAM_OR_ASSEM | TypeAttributes::INTERFACE | TypeAttributes::ABSTRACT;
+        assert_eq!(nested.flag_names(), ["NESTED_FAM_OR_ASSEM", "AUTO_LAYOUT", "INTERFACE", "ABSTRACT", "ANSI_CLASS"]);
+
+        // The special constraints are independent bits rather than one value.
+        let constraints = GenericParamAttributes::REFERENCE_TYPE_CONSTRAINT | GenericParamAttributes::DEFAULT_CONSTRUCTOR_CONSTRAINT;
+        assert_eq!(constraints.flag_names(), ["NONE", "REFERENCE_TYPE_CONSTRAINT", "DEFAULT_CONSTRUCTOR_CONSTRAINT"]);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mapped_image_matches_read_image() {
+        let mapped = PeParser::open_mmap("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let read = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+
+        assert_eq!(mapped.buffer.bytes(), read.buffer.bytes());
+        assert!(mapped.diff(&read).is_empty());
+        assert_eq!(
+            mapped.entry_point_body().unwrap().disassemble(&mapped),
+            read.entry_point_body().unwrap().disassemble(&read),
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn scan_directory_collects_errors_per_file() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let root = image.rva_to_offset(image.cli_header.meta_data.rva).unwrap() as usize;
+
+        // Removes the directory even if an assertion fails.
+        struct TempDir(std::path::PathBuf);
+        impl Drop for TempDir {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_dir_all(&self.0);
+            }
+        }
+        let temp = TempDir(std::env::temp_dir().join(format!("rscil-scan-{}", std::process::id())));
+        let directory = &temp.0;
+        std::fs::create_dir_all(directory.join("nested")).unwrap();
+        // A link back up the tree isn't followed.
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(directory, directory.join("nested").join("loop")).unwrap();
+
+        let mut data = std::fs::read("tests/HelloWorld.exe").unwrap();
+        std::fs::write(directory.join("HelloWorld.exe"), &data).unwrap();
+        std::fs::write(directory.join("notes.txt"), b"not an assembly").unwrap();
+        // Break the signature of the metadata root.
+        data[root] = 0;
+        std::fs::write(directory.join("nested").join("Corrupt.dll"), &data).unwrap();
+
+        let scanned = AtomicUsize::new(0);
+        let errors = scan_directory(directory, |image| {
+            if image.find_type_by_name("", "HelloWorld").is_some() {
+                scanned.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+        assert_eq!(scanned.load(Ordering::Relaxed), 1);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].0.ends_with("nested/Corrupt.dll"));
+
+        // A panicking callback is reported as an error of its file instead of aborting the scan.
+        let errors = scan_directory(directory, |_| panic!("callback failed"));
+        assert_eq!(errors.len(), 2);
+        let panicked = errors.iter().find(|(path, _)| path.ends_with("HelloWorld.exe")).unwrap();
+        assert!(matches!(&panicked.1, LushError::Panic(message) if message == "callback failed"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_tables_to_json() {
+        let image = PeParser::open("tests/HelloWorld.exe")
+            .and_then(|parser| parser.read())
+            .unwrap();
+        let json = image.to_json();
+        assert!(json.starts_with("{\"Module\":[{\"index\":1,"));
+        assert!(json.contains("\"TypeDef\":["));
+        assert!(json.contains("\"extends\":{\"table\":\"TypeRef\",\"index\":"));
+
+        let token = MetadataToken::table(TableKind::MethodDef, 1);
+        assert_eq!(serde_json::to_string(&token).unwrap(), "\"0x06000001\"");
+        let flags = MethodAttributes::PUBLIC | MethodAttributes::STATIC;
+        assert_eq!(serde_json::to_string(&flags).unwrap(), "{\"value\":22,\"names\":[\"PUBLIC\",\"STATIC\",\"REUSE_SLOT\"]}");
+    }
 }
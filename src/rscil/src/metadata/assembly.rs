@@ -0,0 +1,123 @@
+use super::*;
+
+/// The identity of an assembly, e.g. `mscorlib, Version=4.0.0.0, Culture=neutral,
+/// PublicKeyToken=b77a5c561934e089`, see [`PeImage::assembly_name`] and [`PeImage::assembly_references`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyName {
+    pub name: String,
+    /// *MajorVersion*, *MinorVersion*, *BuildNumber* and *RevisionNumber*.
+    pub version: (u16, u16, u16, u16),
+    /// `None` for the neutral culture.
+    pub culture: Option<String>,
+    /// `None` for an assembly that isn't strong named.
+    pub public_key_token: Option<[u8; 8]>,
+}
+
+impl PeImage {
+    /// # [II.22.2] Assembly : 0x20
+    /// [...]
+    ///
+    /// Returns the identity of the assembly, `None` if the image is a module without an *Assembly* row.
+    ///
+    /// [II.22.2]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=237
+    pub fn assembly_name(&self) -> Option<AssemblyName> {
+        let assembly = self.get_assembly()?;
+        Some(AssemblyName {
+            name: self.get_string(assembly.name).into_owned(),
+            version: (assembly.major_version, assembly.minor_version, assembly.build_number, assembly.revision_number),
+            culture: self.culture(assembly.culture),
+            public_key_token: self.get_blob(assembly.public_key)
+                .filter(|key| !key.is_empty())
+                .map(public_key_token),
+        })
+    }
+
+    /// # [II.22.5] AssemblyRef : 0x23
+    /// [...]
+    ///
+    /// Returns the identities of the assemblies the image references, in table order. *PublicKeyOrToken*
+    /// holds the full public key if [`AssemblyFlags::PUBLIC_KEY`] is set, which is hashed to its token.
+    ///
+    /// [II.22.5]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=238
+    pub fn assembly_references(&self) -> Vec<AssemblyName> {
+        self.table::<AssemblyRef>().iter().map(|assembly_ref| {
+            let blob = self.get_blob(assembly_ref.public_key_or_token).filter(|blob| !blob.is_empty());
+            let public_key_token = match blob {
+                Some(key) if assembly_ref.flags.contains(AssemblyFlags::PUBLIC_KEY) => Some(public_key_token(key)),
+                Some(token) => token.try_into().ok(),
+                None => None,
+            };
+
+            AssemblyName {
+                name: self.get_string(assembly_ref.name).into_owned(),
+                version: (assembly_ref.major_version, assembly_ref.minor_version, assembly_ref.build_number, assembly_ref.revision_number),
+                culture: self.culture(assembly_ref.culture),
+                public_key_token,
+            }
+        }).collect()
+    }
+
+    fn culture(&self, index: StringIndex) -> Option<String> {
+        Some(self.get_string(index).into_owned()).filter(|culture| !culture.is_empty())
+    }
+}
+
+/// # II.6.2.1.3 Originator's public key
+/// [...]
+///
+/// The public key token is the last 8 bytes of the SHA-1 hash of the public key, in reverse order.
+pub fn public_key_token(public_key: &[u8]) -> [u8; 8] {
+    let hash = sha1(public_key);
+    let mut token: [u8; 8] = hash[12..].try_into().unwrap();
+    token.reverse();
+    token
+}
+
+/// The SHA-1 digest of `data`, as specified by FIPS 180-4.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    // Pad with a one bit, zeroes up to 56 bytes modulo 64, then the message length in bits.
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend(((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut words = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            words[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, value) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
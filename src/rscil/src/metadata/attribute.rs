@@ -0,0 +1,376 @@
+use super::*;
+
+const ELEMENT_TYPE_BOOLEAN: u8 = 0x02;
+const ELEMENT_TYPE_CHAR: u8 = 0x03;
+const ELEMENT_TYPE_I1: u8 = 0x04;
+const ELEMENT_TYPE_U1: u8 = 0x05;
+const ELEMENT_TYPE_I2: u8 = 0x06;
+const ELEMENT_TYPE_U2: u8 = 0x07;
+const ELEMENT_TYPE_I4: u8 = 0x08;
+const ELEMENT_TYPE_U4: u8 = 0x09;
+const ELEMENT_TYPE_I8: u8 = 0x0a;
+const ELEMENT_TYPE_U8: u8 = 0x0b;
+const ELEMENT_TYPE_R4: u8 = 0x0c;
+const ELEMENT_TYPE_R8: u8 = 0x0d;
+const ELEMENT_TYPE_STRING: u8 = 0x0e;
+const ELEMENT_TYPE_SZARRAY: u8 = 0x1d;
+
+const SERIALIZATION_TYPE_TYPE: u8 = 0x50;
+const SERIALIZATION_TYPE_TAGGED_OBJECT: u8 = 0x51;
+const SERIALIZATION_TYPE_FIELD: u8 = 0x53;
+const SERIALIZATION_TYPE_PROPERTY: u8 = 0x54;
+const SERIALIZATION_TYPE_ENUM: u8 = 0x55;
+
+const PROLOG: u16 = 0x0001;
+
+/// # II.23.3 Custom attributes
+/// [...]
+///
+/// ```text
+/// CustomAttrib ::= Prolog FixedArg* NumNamed NamedArg*
+/// ```
+///
+/// The decoded *CustomAttribute.Value* blob, see [`PeImage::decode_custom_attribute`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomAttributeValue {
+    /// The arguments of the constructor, in the order of its parameters.
+    pub fixed_args: Vec<AttributeArgument>,
+    pub named_args: Vec<NamedArgument>,
+}
+
+/// A single *FixedArg* or the value of a *NamedArg*.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeArgument {
+    Boolean(bool),
+    Char(u16),
+    I1(i8),
+    U1(u8),
+    I2(i16),
+    U2(u16),
+    I4(i32),
+    U4(u32),
+    I8(i64),
+    U8(u64),
+    R4(f32),
+    R8(f64),
+    /// A *SerString*, `None` for a null string.
+    String(Option<String>),
+    /// A `System.Type`, stored as the canonical name of the type, `None` for null.
+    Type(Option<String>),
+    /// A value of an enum type, stored as its underlying integer type.
+    Enum { type_name: String, value: Box<AttributeArgument> },
+    /// A single-dimensional, zero-based array, `None` for a null array.
+    Array(Option<Vec<AttributeArgument>>),
+}
+
+/// Whether a *NamedArg* sets a field or a property of the attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamedArgumentKind {
+    Field,
+    Property,
+}
+
+/// # II.23.3 Custom attributes
+/// [...]
+///
+/// ```text
+/// NamedArg ::= (FIELD | PROPERTY) FieldOrPropType FieldOrPropName FixedArg
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedArgument {
+    pub kind: NamedArgumentKind,
+    pub name: String,
+    pub value: AttributeArgument,
+}
+
+/// # [II.22.11] DeclSecurity : 0x0E
+/// [...]
+///
+/// The decoded *PermissionSet* blob of a *DeclSecurity* row, see [`PeImage::decode_declarative_security`].
+///
+/// [II.22.11]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=244
+#[derive(Debug, Clone, PartialEq)]
+pub enum PermissionSet {
+    /// The binary format: a `.` followed by a compressed count of attributes.
+    Attributes(Vec<SecurityAttribute>),
+    /// The legacy XML format of .NET 1.x, as UTF-16 text starting with `<`.
+    Xml(String),
+}
+
+/// A security attribute in the binary format of a [`PermissionSet`]: the assembly-qualified name of the
+/// attribute type, followed by a compressed byte length and count of its *NamedArg*s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityAttribute {
+    pub type_name: String,
+    pub named_args: Vec<NamedArgument>,
+}
+
+/// The type of an argument as far as its serialized form is concerned.
+#[derive(Debug, Clone)]
+enum ElemType {
+    /// One of the `ELEMENT_TYPE_*` values of a primitive type.
+    Primitive(u8),
+    String,
+    Type,
+    /// `System.Object`, the value is preceded by its *FieldOrPropType*.
+    Boxed,
+    Enum { type_name: String, underlying: u8 },
+    Array(Box<ElemType>),
+}
+
+impl PeImage {
+    /// # II.23.3 Custom attributes
+    /// [...]
+    ///
+    /// Decodes the *Value* blob of a *CustomAttribute* row. The blob doesn't describe the types of the
+    /// fixed arguments, so they are taken from the signature of the attribute constructor (*Type*).
+    ///
+    /// Enums defined in this image are read with the type of their `value__` field, enums defined
+    /// elsewhere are assumed to be `int32` as their definition isn't available.
+    pub fn decode_custom_attribute(&self, row: &CustomAttribute) -> Result<CustomAttributeValue, LushError> {
+        let constructor = self.attribute_constructor(row.type_)?;
+        let mut data = self.get_blob(row.value)
+            .ok_or(LushError::InvalidSignature(row.value.0))?;
+
+        let prolog = data.read_u16::<LittleEndian>().map_err(invalid)?;
+        if prolog != PROLOG {
+            return Err(LushError::InvalidBlob(format!("expected custom attribute prolog 0x0001, found 0x{:04X}", prolog)));
+        }
+
+        let fixed_args = constructor.params.iter()
+            .map(|param| {
+                let elem_type = self.elem_type_of(param)?;
+                self.read_argument(&mut data, &elem_type)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // NumNamed is mandatory, but some compilers omit it when there are no named arguments.
+        let named_count = match data.is_empty() {
+            true => 0,
+            false => data.read_u16::<LittleEndian>().map_err(invalid)?,
+        };
+        let named_args = (0..named_count)
+            .map(|_| self.read_named_argument(&mut data))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CustomAttributeValue { fixed_args, named_args })
+    }
+
+    /// Decodes the *PermissionSet* blob of a *DeclSecurity* row, see [`PermissionSet`]. The enum types of
+    /// the named arguments are resolved like for [`PeImage::decode_custom_attribute`].
+    pub fn decode_declarative_security(&self, row: &DeclSecurity) -> Result<PermissionSet, LushError> {
+        let data = self.get_blob(row.permission_set)
+            .ok_or(LushError::InvalidSignature(row.permission_set.0))?;
+        self.read_permission_set(data)
+    }
+
+    pub(crate) fn read_permission_set(&self, mut data: &[u8]) -> Result<PermissionSet, LushError> {
+        match data.first() {
+            Some(b'.') => data = &data[1..],
+            Some(b'<') => {
+                let units: Vec<u16> = data.chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+                return Ok(PermissionSet::Xml(String::from_utf16_lossy(&units)));
+            }
+            _ => return Err(LushError::InvalidBlob("permission set starts with neither '.' nor '<'".to_string())),
+        }
+
+        let (count, _) = read_compressed_u32(&mut data).map_err(invalid)?;
+        let attributes = (0..count)
+            .map(|_| {
+                let type_name = read_ser_string(&mut data)?
+                    .ok_or_else(|| LushError::InvalidBlob("security attribute without a type name".to_string()))?;
+
+                let (length, _) = read_compressed_u32(&mut data).map_err(invalid)?;
+                if data.len() < length as usize {
+                    return Err(LushError::InvalidBlob("unexpected end of permission set".to_string()));
+                }
+                let (mut arguments, rest) = data.split_at(length as usize);
+                data = rest;
+
+                let (named_count, _) = read_compressed_u32(&mut arguments).map_err(invalid)?;
+                let named_args = (0..named_count)
+                    .map(|_| self.read_named_argument(&mut arguments))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(SecurityAttribute { type_name, named_args })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PermissionSet::Attributes(attributes))
+    }
+
+    fn attribute_constructor(&self, index: CodedIndex) -> Result<MethodSignature, LushError> {
+        let signature = match index.table {
+            TableKind::MethodDef => self.get_method_def(index.index).map(|method| method.signature),
+            TableKind::MemberRef => self.get_member_ref(index.index).map(|member_ref| member_ref.signature),
+            _ => None,
+        }.ok_or_else(|| LushError::InvalidBlob(format!("{:?} {} is not an attribute constructor", index.table, index.index)))?;
+
+        let blob = self.get_blob(signature).ok_or(LushError::InvalidSignature(signature.0))?;
+        MethodSignature::parse(blob, self.streams.metadata.context())
+            .map_err(|_| LushError::InvalidSignature(signature.0))
+    }
+
+    fn elem_type_of(&self, param: &SigType) -> Result<ElemType, LushError> {
+        if let Some(element_type) = primitive_element_type(param) {
+            return Ok(ElemType::Primitive(element_type));
+        }
+
+        Ok(match param {
+            SigType::String => ElemType::String,
+            SigType::Object => ElemType::Boxed,
+            SigType::SzArray(element) => ElemType::Array(Box::new(self.elem_type_of(element)?)),
+            SigType::Modified { inner, .. } => self.elem_type_of(inner)?,
+            SigType::Class(index) if self.type_name(*index).as_deref() == Some("System.Type") => ElemType::Type,
+            SigType::ValueType(index) => {
+                let type_name = self.type_name(*index)
+                    .ok_or_else(|| LushError::InvalidBlob(format!("{:?} {} is not an enum", index.table, index.index)))?;
+                let underlying = self.enum_underlying_type(&type_name)?;
+                ElemType::Enum { type_name, underlying }
+            }
+            other => return Err(LushError::InvalidBlob(format!("{:?} can't be the type of an attribute argument", other))),
+        })
+    }
+
+    /// Returns the `ELEMENT_TYPE_*` of the `value__` field of the enum `type_name`, `int32` if the enum
+    /// isn't defined in this image. Fails if `value__` isn't of a primitive type, e.g. of the enum itself,
+    /// which would otherwise have to be resolved again.
+    fn enum_underlying_type(&self, type_name: &str) -> Result<u8, LushError> {
+        // Enum names in blobs are assembly qualified: "Namespace.Name, Assembly, Version=..."
+        let type_name = type_name.split(',').next().unwrap_or_default().trim();
+
+        let signature = self.table::<TypeDef>()
+            .iter()
+            .find(|type_def| self.full_type_name(type_def.type_namespace, type_def.type_name) == type_name)
+            .and_then(|type_def| {
+                self.fields_of(type_def)
+                    .into_iter()
+                    .find(|field| self.get_string(field.name) == "value__")
+            })
+            .and_then(|field| self.get_field_signature(field).ok());
+
+        match signature {
+            Some(signature) => primitive_element_type(&signature.field_type)
+                .ok_or_else(|| LushError::InvalidBlob(format!("value__ of enum {} is a {:?}", type_name, signature.field_type))),
+            None => Ok(ELEMENT_TYPE_I4),
+        }
+    }
+
+    fn read_named_argument(&self, data: &mut &[u8]) -> Result<NamedArgument, LushError> {
+        let kind = match data.read_u8().map_err(invalid)? {
+            SERIALIZATION_TYPE_FIELD => NamedArgumentKind::Field,
+            SERIALIZATION_TYPE_PROPERTY => NamedArgumentKind::Property,
+            other => return Err(LushError::InvalidBlob(format!("expected FIELD or PROPERTY, found 0x{:02X}", other))),
+        };
+        let elem_type = self.read_field_or_prop_type(data)?;
+        let name = read_ser_string(data)?
+            .ok_or_else(|| LushError::InvalidBlob("named argument without a name".to_string()))?;
+        let value = self.read_argument(data, &elem_type)?;
+
+        Ok(NamedArgument { kind, name, value })
+    }
+
+    /// ```text
+    /// FieldOrPropType ::= BOOLEAN | CHAR | I1 | U1 | I2 | U2 | I4 | U4 | I8 | U8 | R4 | R8 | STRING
+    ///                   | TYPE | TAGGED_OBJECT | SZARRAY FieldOrPropType | ENUM SerString
+    /// ```
+    fn read_field_or_prop_type(&self, data: &mut &[u8]) -> Result<ElemType, LushError> {
+        Ok(match data.read_u8().map_err(invalid)? {
+            element_type @ ELEMENT_TYPE_BOOLEAN..=ELEMENT_TYPE_R8 => ElemType::Primitive(element_type),
+            ELEMENT_TYPE_STRING => ElemType::String,
+            SERIALIZATION_TYPE_TYPE => ElemType::Type,
+            SERIALIZATION_TYPE_TAGGED_OBJECT => ElemType::Boxed,
+            ELEMENT_TYPE_SZARRAY => ElemType::Array(Box::new(self.read_field_or_prop_type(data)?)),
+            SERIALIZATION_TYPE_ENUM => {
+                let type_name = read_ser_string(data)?
+                    .ok_or_else(|| LushError::InvalidBlob("enum argument without a type name".to_string()))?;
+                let underlying = self.enum_underlying_type(&type_name)?;
+                ElemType::Enum { type_name, underlying }
+            }
+            other => return Err(LushError::InvalidBlob(format!("0x{:02X} is not a FieldOrPropType", other))),
+        })
+    }
+
+    fn read_argument(&self, data: &mut &[u8], elem_type: &ElemType) -> Result<AttributeArgument, LushError> {
+        Ok(match elem_type {
+            ElemType::Primitive(element_type) => read_primitive(data, *element_type)?,
+            ElemType::String => AttributeArgument::String(read_ser_string(data)?),
+            ElemType::Type => AttributeArgument::Type(read_ser_string(data)?),
+            ElemType::Boxed => {
+                let elem_type = self.read_field_or_prop_type(data)?;
+                self.read_argument(data, &elem_type)?
+            }
+            ElemType::Enum { type_name, underlying } => AttributeArgument::Enum {
+                type_name: type_name.clone(),
+                value: Box::new(read_primitive(data, *underlying)?),
+            },
+            ElemType::Array(element) => {
+                // NumElem is 0xFFFFFFFF for a null array.
+                let count = data.read_u32::<LittleEndian>().map_err(invalid)?;
+                match count {
+                    u32::MAX => AttributeArgument::Array(None),
+                    count => AttributeArgument::Array(Some((0..count)
+                        .map(|_| self.read_argument(data, element))
+                        .collect::<Result<Vec<_>, _>>()?)),
+                }
+            }
+        })
+    }
+}
+
+/// The `ELEMENT_TYPE_*` of a primitive type an attribute argument can have, `None` for any other type.
+fn primitive_element_type(param: &SigType) -> Option<u8> {
+    Some(match param {
+        SigType::Boolean => ELEMENT_TYPE_BOOLEAN,
+        SigType::Char => ELEMENT_TYPE_CHAR,
+        SigType::I1 => ELEMENT_TYPE_I1,
+        SigType::U1 => ELEMENT_TYPE_U1,
+        SigType::I2 => ELEMENT_TYPE_I2,
+        SigType::U2 => ELEMENT_TYPE_U2,
+        SigType::I4 => ELEMENT_TYPE_I4,
+        SigType::U4 => ELEMENT_TYPE_U4,
+        SigType::I8 => ELEMENT_TYPE_I8,
+        SigType::U8 => ELEMENT_TYPE_U8,
+        SigType::R4 => ELEMENT_TYPE_R4,
+        SigType::R8 => ELEMENT_TYPE_R8,
+        _ => return None,
+    })
+}
+
+fn read_primitive(data: &mut &[u8], element_type: u8) -> Result<AttributeArgument, LushError> {
+    Ok(match element_type {
+        ELEMENT_TYPE_BOOLEAN => AttributeArgument::Boolean(data.read_u8().map_err(invalid)? != 0),
+        ELEMENT_TYPE_CHAR => AttributeArgument::Char(data.read_u16::<LittleEndian>().map_err(invalid)?),
+        ELEMENT_TYPE_I1 => AttributeArgument::I1(data.read_i8().map_err(invalid)?),
+        ELEMENT_TYPE_U1 => AttributeArgument::U1(data.read_u8().map_err(invalid)?),
+        ELEMENT_TYPE_I2 => AttributeArgument::I2(data.read_i16::<LittleEndian>().map_err(invalid)?),
+        ELEMENT_TYPE_U2 => AttributeArgument::U2(data.read_u16::<LittleEndian>().map_err(invalid)?),
+        ELEMENT_TYPE_I4 => AttributeArgument::I4(data.read_i32::<LittleEndian>().map_err(invalid)?),
+        ELEMENT_TYPE_U4 => AttributeArgument::U4(data.read_u32::<LittleEndian>().map_err(invalid)?),
+        ELEMENT_TYPE_I8 => AttributeArgument::I8(data.read_i64::<LittleEndian>().map_err(invalid)?),
+        ELEMENT_TYPE_U8 => AttributeArgument::U8(data.read_u64::<LittleEndian>().map_err(invalid)?),
+        ELEMENT_TYPE_R4 => AttributeArgument::R4(data.read_f32::<LittleEndian>().map_err(invalid)?),
+        ELEMENT_TYPE_R8 => AttributeArgument::R8(data.read_f64::<LittleEndian>().map_err(invalid)?),
+        other => return Err(LushError::InvalidBlob(format!("0x{:02X} is not a primitive element type", other))),
+    })
+}
+
+/// A *SerString*: a compressed length followed by that many bytes of UTF-8, or a single 0xFF byte for
+/// a null string.
+fn read_ser_string(data: &mut &[u8]) -> Result<Option<String>, LushError> {
+    if data.first() == Some(&0xFF) {
+        *data = &data[1..];
+        return Ok(None);
+    }
+
+    let (length, _) = read_compressed_u32(data).map_err(invalid)?;
+    if data.len() < length as usize {
+        return Err(LushError::InvalidBlob("unexpected end of custom attribute".to_string()));
+    }
+    let (string, rest) = data.split_at(length as usize);
+    *data = rest;
+    Ok(Some(String::from_utf8_lossy(string).into_owned()))
+}
+
+fn invalid(error: std::io::Error) -> LushError {
+    LushError::InvalidBlob(format!("invalid custom attribute: {}", error))
+}
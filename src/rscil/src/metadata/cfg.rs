@@ -0,0 +1,87 @@
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use super::*;
+
+/// A run of instructions with a single entry at its first instruction and a single exit at its last one,
+/// see [`MethodBody::basic_blocks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// The IL offset of the first instruction of the block.
+    pub start_offset: u32,
+    /// The instructions of the block, as a range of indices into [`MethodBody::body`].
+    pub instructions: Range<usize>,
+    /// The start offsets of the blocks control may pass to from the end of this one, explicit branch
+    /// targets first (in operand order for `switch`) followed by the fall-through block.
+    pub successors: Vec<u32>,
+}
+
+impl MethodBody {
+    /// Splits the method body into its control-flow graph of [`BasicBlock`]s, in IL order.
+    ///
+    /// A block starts at the first instruction, at every branch target, after every branch, `switch`,
+    /// return or throw, and at the boundaries of the protected blocks, handlers and filters of the
+    /// [`MethodBody::exception_clauses`]. Handlers are only entered by the runtime, so they have no
+    /// predecessors in the graph. A branch target which isn't the start of an instruction is still
+    /// listed as a successor, but can't start a block.
+    pub fn basic_blocks(&self) -> Vec<BasicBlock> {
+        let Some(first) = self.body.first() else {
+            return Vec::new();
+        };
+
+        let mut leaders = BTreeSet::from([first.offset]);
+        for instruction in &self.body {
+            leaders.extend(instruction.branch_targets());
+            if !falls_through(instruction) || !instruction.opcode.branch_targets().is_empty() {
+                leaders.insert(instruction.offset + instruction.opcode.encoded_size());
+            }
+        }
+        for clause in &self.exception_clauses {
+            leaders.extend([
+                clause.try_offset,
+                clause.try_offset.saturating_add(clause.try_length),
+                clause.handler_offset,
+                clause.handler_offset.saturating_add(clause.handler_length),
+            ]);
+            if clause.flags == ExceptionClauseKind::Filter {
+                leaders.insert(clause.class_token_or_filter);
+            }
+        }
+
+        let starts: Vec<usize> = self.body.iter()
+            .enumerate()
+            .filter(|(_, instruction)| leaders.contains(&instruction.offset))
+            .map(|(index, _)| index)
+            .collect();
+
+        starts.iter().enumerate().map(|(i, &start)| {
+            let end = starts.get(i + 1).copied().unwrap_or(self.body.len());
+            let last = &self.body[end - 1];
+
+            let mut successors = Vec::new();
+            let fall_through = self.body.get(end).filter(|_| falls_through(last)).map(|next| next.offset);
+            for offset in last.branch_targets().into_iter().chain(fall_through) {
+                if !successors.contains(&offset) {
+                    successors.push(offset);
+                }
+            }
+
+            BasicBlock {
+                start_offset: self.body[start].offset,
+                instructions: start..end,
+                successors,
+            }
+        }).collect()
+    }
+}
+
+/// Whether control may continue with the instruction following `instruction`.
+fn falls_through(instruction: &Instruction) -> bool {
+    let code = instruction.opcode.code();
+    match code.metadata().flow_control {
+        FlowControl::Branch | FlowControl::Return | FlowControl::Throw => false,
+        // jmp transfers control to the called method and never comes back.
+        FlowControl::Call => !matches!(code, Code::Jmp),
+        _ => true,
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use paste::paste;
 
 use super::*;
@@ -7,8 +9,9 @@ pub struct MethodBody {
     pub body: Vec<Instruction>,
     pub max_stack: u16,
     pub code_size: u32,
-    // TODO: Exception handling
-    // TODO: Local variables
+    pub exception_clauses: Vec<ExceptionClause>,
+    /// The *StandAloneSig* describing the local variables of the method, `None` if it has no locals.
+    pub local_var_sig_token: Option<MetadataToken>,
 }
 
 impl MethodBody {
@@ -32,6 +35,8 @@ impl MethodBody {
             body: Vec::with_capacity(code_size as usize),
             max_stack: 8,
             code_size,
+            exception_clauses: Vec::new(),
+            local_var_sig_token: None,
         }
     }
 
@@ -55,12 +60,226 @@ impl MethodBody {
         let _flags: u16 = u16::from_le_bytes([bytes[0], bytes[1]]);
         let max_stack :u16 = u16::from_le_bytes([bytes[2], bytes[3]]);
         let code_size: u32 = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
-        let _local_var_sig_tok: u32 = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+        let local_var_sig_tok: u32 = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
         
         MethodBody {
-            body: Vec::with_capacity(code_size as usize),
+            // The code size isn't checked against the image yet, so don't reserve room for it.
+            body: Vec::new(),
             max_stack,
             code_size,
+            exception_clauses: Vec::new(),
+            local_var_sig_token: (local_var_sig_tok != 0).then(|| MetadataToken::from_raw(local_var_sig_tok)),
+        }
+    }
+
+    /// # [II.25.4.5] Method data section 
+    /// 
+    /// At the next 4-byte boundary following the method body can be extra method data sections. [...]
+    /// 
+    /// Reads the data sections following the IL code of a method whose header has
+    /// [`MethodHeaderType::COR_IL_METHOD_MORE_SECTS`] set, returning the exception handling clauses of all
+    /// `CorILMethod_Sect_EHTable` sections. Other sections are skipped.
+    /// 
+    /// [II.25.4.5]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=312
+    pub fn read_sections(buffer: &mut Cursor<&[u8]>) -> Result<Vec<ExceptionClause>, std::io::Error> {
+        let mut clauses = Vec::new();
+
+        loop {
+            buffer.set_position((buffer.position() + 3) & !3);
+
+            let kind = MethodSectionFlags::new(buffer.read_u8()?);
+            let is_fat = kind.contains(MethodSectionFlags::COR_IL_METHOD_SECT_FAT_FORMAT);
+            let data_size = match is_fat {
+                true => buffer.read_u24::<LittleEndian>()?,
+                false => {
+                    let data_size = buffer.read_u8()? as u32;
+                    buffer.read_u16::<LittleEndian>()?;
+                    data_size
+                }
+            };
+            // The data size includes the 4 byte section header.
+            let data_size = data_size.checked_sub(4).ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid method data section size")
+            })?;
+
+            if kind.contains(MethodSectionFlags::COR_IL_METHOD_SECT_EH_TABLE) {
+                let clause_size = if is_fat { 24 } else { 12 };
+                for _ in 0..data_size / clause_size {
+                    clauses.push(match is_fat {
+                        true => ExceptionClause::fat(buffer)?,
+                        false => ExceptionClause::small(buffer)?,
+                    });
+                }
+            } else {
+                buffer.seek(SeekFrom::Current(data_size as i64))?;
+            }
+
+            if !kind.contains(MethodSectionFlags::COR_IL_METHOD_SECT_MORE_SECTS) {
+                return Ok(clauses);
+            }
+        }
+    }
+
+    /// Maps the IL offset of every instruction to its index in [`MethodBody::body`].
+    pub fn offset_map(&self) -> HashMap<u32, usize> {
+        self.body.iter()
+            .enumerate()
+            .map(|(index, instruction)| (instruction.offset, index))
+            .collect()
+    }
+
+    /// Returns the instruction starting at the given IL offset, `None` if the offset is outside the method
+    /// body or lands in the middle of an instruction.
+    pub fn instruction_at(&self, offset: u32) -> Option<&Instruction> {
+        self.body.binary_search_by_key(&offset, |instruction| instruction.offset)
+            .ok()
+            .map(|index| &self.body[index])
+    }
+
+    /// Returns the prefixes applying to the instruction at the given IL offset, i.e. the run of prefix
+    /// instructions directly before it, in IL order. Empty if the instruction has no prefixes or there's
+    /// no instruction at `offset`, see [`Code::is_prefix`].
+    ///
+    /// Prefixes are kept as instructions of their own in [`MethodBody::body`], as a branch may target them.
+    pub fn prefixes(&self, offset: u32) -> &[Instruction] {
+        let Ok(index) = self.body.binary_search_by_key(&offset, |instruction| instruction.offset) else {
+            return &[];
+        };
+        let start = self.body[..index].iter()
+            .rposition(|instruction| !instruction.opcode.code().is_prefix())
+            .map_or(0, |last| last + 1);
+        &self.body[start..index]
+    }
+
+    /// Resolves the [`BranchTarget`]s of every branch and `switch` instruction to IL offsets and
+    /// instruction indices. Called by [`PeParser::read_method_body`] once all instructions are parsed.
+    ///
+    /// Fails if a target lies before the start of the method body or past `u32::MAX`.
+    pub fn resolve_targets(&mut self) -> Result<(), std::io::Error> {
+        let offsets: Vec<u32> = self.body.iter().map(|instruction| instruction.offset).collect();
+
+        for i in 0..self.body.len() {
+            let next = offsets.get(i + 1).copied().unwrap_or(self.code_size);
+            let offset = offsets[i];
+            for target in self.body[i].opcode.branch_targets_mut() {
+                target.offset = u32::try_from(next as i64 + target.delta as i64).map_err(|_| std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Branch target of IL_{:04x} is out of range", offset),
+                ))?;
+                target.index = offsets.binary_search(&target.offset).ok();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// # II.25.4.6 Exception handling clauses 
+/// 
+/// [...]
+/// 
+/// The following flag values are used for each exception handling clause: 
+/// 
+/// | Flag                                | Value    | Description |
+/// | ----------------------------------- | -------- | ----------- |
+/// | `COR_ILEXCEPTION_CLAUSE_EXCEPTION`  | `0x0000` | A typed exception clause |
+/// | `COR_ILEXCEPTION_CLAUSE_FILTER`     | `0x0001` | An exception filter and handler clause |
+/// | `COR_ILEXCEPTION_CLAUSE_FINALLY`    | `0x0002` | A finally clause |
+/// | `COR_ILEXCEPTION_CLAUSE_FAULT`      | `0x0004` | Fault clause (finally that is called on exception only) |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionClauseKind {
+    Exception,
+    Filter,
+    Finally,
+    Fault,
+    Unknown(u32),
+}
+
+impl From<u32> for ExceptionClauseKind {
+    fn from(value: u32) -> Self {
+        match value {
+            0x0000 => ExceptionClauseKind::Exception,
+            0x0001 => ExceptionClauseKind::Filter,
+            0x0002 => ExceptionClauseKind::Finally,
+            0x0004 => ExceptionClauseKind::Fault,
+            value => ExceptionClauseKind::Unknown(value),
+        }
+    }
+}
+
+/// # II.25.4.6 Exception handling clauses 
+/// 
+/// Exception handling clauses also come in small and fat versions. 
+/// 
+/// Offsets and lengths are in bytes of IL, relative to the start of the method body.
+/// `class_token_or_filter` is the metadata token of the caught type for [`ExceptionClauseKind::Exception`],
+/// the offset of the filter code for [`ExceptionClauseKind::Filter`] and unused otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionClause {
+    pub flags: ExceptionClauseKind,
+    pub try_offset: u32,
+    pub try_length: u32,
+    pub handler_offset: u32,
+    pub handler_length: u32,
+    pub class_token_or_filter: u32,
+}
+
+impl ExceptionClause {
+    /// The small form of the exception clause should be used whenever the code sizes for the try block and
+    /// handler code are both smaller than 256 bytes and both their offsets are smaller than 65536. 
+    /// 
+    /// | Offset | Size | Field             | Description |
+    /// | ------ | ---- | ----------------- | ----------- |
+    /// | 0      | 2    | **Flags**         | Flags, see above. |
+    /// | 2      | 2    | **TryOffset**     | Offset in bytes of try block from start of method body. |
+    /// | 4      | 1    | **TryLength**     | Length in bytes of the try block |
+    /// | 5      | 2    | **HandlerOffset** | Location of the handler for this try block |
+    /// | 7      | 1    | **HandlerLength** | Size of the handler code in bytes |
+    /// | 8      | 4    | **ClassToken**    | Meta data token for a type-based exception handler |
+    /// | 8      | 4    | **FilterOffset**  | Offset in method body for filter-based exception handler |
+    pub fn small(buffer: &mut impl Read) -> Result<ExceptionClause, std::io::Error> {
+        Ok(ExceptionClause {
+            flags: ExceptionClauseKind::from(buffer.read_u16::<LittleEndian>()? as u32),
+            try_offset: buffer.read_u16::<LittleEndian>()? as u32,
+            try_length: buffer.read_u8()? as u32,
+            handler_offset: buffer.read_u16::<LittleEndian>()? as u32,
+            handler_length: buffer.read_u8()? as u32,
+            class_token_or_filter: buffer.read_u32::<LittleEndian>()?,
+        })
+    }
+
+    /// The fat form has the same fields as [`ExceptionClause::small`], each stored as a 4-byte integer.
+    pub fn fat(buffer: &mut impl Read) -> Result<ExceptionClause, std::io::Error> {
+        Ok(ExceptionClause {
+            flags: ExceptionClauseKind::from(buffer.read_u32::<LittleEndian>()?),
+            try_offset: buffer.read_u32::<LittleEndian>()?,
+            try_length: buffer.read_u32::<LittleEndian>()?,
+            handler_offset: buffer.read_u32::<LittleEndian>()?,
+            handler_length: buffer.read_u32::<LittleEndian>()?,
+            class_token_or_filter: buffer.read_u32::<LittleEndian>()?,
+        })
+    }
+}
+
+/// The target of a branch or `switch` instruction.
+///
+/// The operand is encoded as a signed delta from the beginning of the instruction following the branch,
+/// see [`MethodBody::resolve_targets`] for how `offset` and `index` are filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BranchTarget {
+    /// The raw operand of the instruction.
+    pub delta: i32,
+    /// The IL offset of the target, relative to the start of the method body.
+    pub offset: u32,
+    /// The index into [`MethodBody::body`] of the instruction at `offset`, `None` if no instruction starts there.
+    pub index: Option<usize>,
+}
+
+impl BranchTarget {
+    pub fn new(delta: i32) -> BranchTarget {
+        BranchTarget {
+            delta,
+            offset: 0,
+            index: None,
         }
     }
 }
@@ -71,6 +290,30 @@ pub struct Instruction {
     pub offset: u32,
 }
 
+impl Instruction {
+    /// Returns the IL offsets targeted by a branch or `switch` instruction, relative to the start of the
+    /// method body. Empty for any other instruction. Targets before the start of the method body or past
+    /// `u32::MAX` are left out.
+    pub fn branch_targets(&self) -> Vec<u32> {
+        let next = self.offset as i64 + self.opcode.encoded_size() as i64;
+        self.opcode.branch_targets()
+            .iter()
+            .filter_map(|target| u32::try_from(next + target.delta as i64).ok())
+            .collect()
+    }
+}
+
+impl Code {
+    /// # III.2 Prefixes to instructions
+    /// [...]
+    ///
+    /// Whether the opcode is a prefix (`constrained.`, `no.`, `readonly.`, `tail.`, `unaligned.` or
+    /// `volatile.`), which modifies the instruction following it rather than doing anything on its own.
+    pub fn is_prefix(&self) -> bool {
+        matches!(self, Code::Constrained | Code::No | Code::Readonly | Code::Tailcall | Code::Unaligned | Code::Volatile)
+    }
+}
+
 #[derive(Debug)]
 pub struct CodeMetadata {
     pub code: Code,
@@ -159,6 +402,21 @@ pub enum FlowControl {
     Meta
 }
 
+/// The in-line argument of an instruction, see [`OpCode::operand`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand<'a> {
+    None,
+    /// An `int8`, `int32` or `int64` constant.
+    Int(i64),
+    /// A `float32` or `float64` constant.
+    Float(f64),
+    /// The number of an argument or local variable.
+    Variable(u16),
+    Token(MetadataToken),
+    Target(&'a BranchTarget),
+    Switch(&'a [BranchTarget]),
+}
+
 /// # III.3.66 switch – table switch based on value 
 /// 
 /// [...]
@@ -166,11 +424,11 @@ pub enum FlowControl {
 /// The format of the instruction is an unsigned int32 representing the number of targets N,
 /// followed by N int32 values specifying jump targets:
 /// these targets are represented as offsets (positive or negative) from the beginning of the instruction following this switch instruction.
-fn read_switch_table(buffer: &mut Buffer) -> Result<Vec<i32>, std::io::Error> {
+fn read_switch_table(buffer: &mut impl Read) -> Result<Vec<BranchTarget>, std::io::Error> {
     let count = buffer.read_u32::<LittleEndian>()? as usize;
     let mut table = Vec::with_capacity(count);
     for _ in 0..count {
-        table.push(buffer.read_i32::<LittleEndian>()?);
+        table.push(BranchTarget::new(buffer.read_i32::<LittleEndian>()?));
     }
     Ok(table)
 }
@@ -179,12 +437,12 @@ macro_rules! opcodes {
     ($(OPDEF($name:ident, $instr:tt, $pop:ident, $push:ident, $operand:ident, $_type:ident, $size:tt, $op1:tt, $op2:tt, $flow:ident))*) => {
         paste! {
             opcodes!(@def $(
-                [<$name:camel>], $instr, $pop, $push, $operand, $op1, $op2, $flow
+                [<$name:camel>], $instr, $pop, $push, $operand, $size, $op1, $op2, $flow
             )*);
         }
     };
 
-    (@def $($name:ident, $instr:tt, $pop:ident, $push:ident, $operand:ident, $op1:tt, $op2:tt, $flow:ident)*) => {
+    (@def $($name:ident, $instr:tt, $pop:ident, $push:ident, $operand:ident, $size:tt, $op1:tt, $op2:tt, $flow:ident)*) => {
         paste! {
             #[derive(Debug)]
             pub enum Code {
@@ -204,12 +462,15 @@ macro_rules! opcodes {
                     }
                 }
 
-                pub fn from(slice: &[u8]) -> Code {
+                /// Looks up the opcode from its two bytes in the order of Partition VI.C.2: `0xFE xx` for a
+                /// two-byte opcode, and `0xFF xx` for a one-byte opcode `xx`. Returns `None` for an
+                /// undefined opcode.
+                pub fn from(slice: &[u8]) -> Option<Code> {
                     match slice {
                         $(
-                            [$op2, $op1, ..] => Code::$name,
+                            [$op1, $op2, ..] => Some(Code::$name),
                         )*
-                        _ => panic!("Invalid opcode: {:?}", slice),
+                        _ => None,
                     }
                 }
             }
@@ -228,7 +489,43 @@ macro_rules! opcodes {
                     }
                 }
 
-                pub fn parse(code: Code, buffer: &mut Buffer) -> Result<OpCode, std::io::Error> {
+                /// Returns the number of bytes the instruction occupies in the IL stream, i.e. the size of the
+                /// opcode plus the size of its in-line argument.
+                pub fn encoded_size(&self) -> u32 {
+                    match self {
+                        $(
+                            OpCode::$name (_operand) => $size + opcodes!(@size $operand _operand),
+                        )*
+                    }
+                }
+
+                /// Returns the in-line argument of the instruction.
+                pub fn operand(&self) -> Operand<'_> {
+                    match self {
+                        $(
+                            OpCode::$name (_operand) => opcodes!(@operand $operand _operand),
+                        )*
+                    }
+                }
+
+                /// Returns the branch targets of a branch or `switch` instruction, empty for any other instruction.
+                pub fn branch_targets(&self) -> Vec<&BranchTarget> {
+                    match self {
+                        $(
+                            OpCode::$name (_operand) => opcodes!(@targets $operand _operand),
+                        )*
+                    }
+                }
+
+                fn branch_targets_mut(&mut self) -> Vec<&mut BranchTarget> {
+                    match self {
+                        $(
+                            OpCode::$name (_operand) => opcodes!(@targets $operand _operand),
+                        )*
+                    }
+                }
+
+                pub fn parse(code: Code, buffer: &mut impl Read) -> Result<OpCode, std::io::Error> {
                     match code {
                         $(
                             Code::$name => Ok(OpCode::$name( opcodes!(@parse $operand buffer) )),
@@ -244,8 +541,8 @@ macro_rules! opcodes {
     // The possible values here are the following:
     //
     // a. InlineBrTarget – Branch target, represented as a 4-byte signed integer from the beginning of the instruction following the current instruction. 
-    (@ty InlineBrTarget) => { i32 };
-    (@parse InlineBrTarget $buffer:ident) => { $buffer.read_i32::<LittleEndian>()? };
+    (@ty InlineBrTarget) => { BranchTarget };
+    (@parse InlineBrTarget $buffer:ident) => { BranchTarget::new($buffer.read_i32::<LittleEndian>()?) };
 
     // b. InlineField – Metadata token (4 bytes) representing a FieldRef (i.e., a MemberRef to a field) or FieldDef 
     (@ty InlineField) => { MetadataToken };
@@ -280,7 +577,7 @@ macro_rules! opcodes {
     (@parse InlineString $buffer:ident) => { MetadataToken::read($buffer)? };
 
     // j. InlineSwitch – Special for the switch instructions, see [`PeParser::read_switch_table`] for details
-    (@ty InlineSwitch) => { Vec<i32> };
+    (@ty InlineSwitch) => { Vec<BranchTarget> };
     (@parse InlineSwitch $buffer:ident) => { read_switch_table($buffer)? };
 
     // k. InlineTok – Arbitrary metadata token (4 bytes) , used for ldtoken instruction, see Partition III for details 
@@ -296,8 +593,8 @@ macro_rules! opcodes {
     (@parse InlineVar $buffer:ident) => { $buffer.read_u16::<LittleEndian>()? };
 
     // n. ShortInlineBrTarget – Short branch target, represented as 1 signed byte from the beginning of the instruction following the current instruction.
-    (@ty ShortInlineBrTarget) => { i8 }; 
-    (@parse ShortInlineBrTarget $buffer:ident) => { $buffer.read_i8()? };
+    (@ty ShortInlineBrTarget) => { BranchTarget };
+    (@parse ShortInlineBrTarget $buffer:ident) => { BranchTarget::new($buffer.read_i8()? as i32) };
 
     // o. ShortInlineI – 1-byte integer, signed or unsigned depending on instruction 
     (@ty ShortInlineI) => { i8 };
@@ -310,6 +607,34 @@ macro_rules! opcodes {
     // q. ShortInlineVar – 1-byte integer representing an argument or local variable 
     (@ty ShortInlineVar) => { u8 };
     (@parse ShortInlineVar $buffer:ident) => { $buffer.read_u8()? };
+
+    (@size InlineSwitch $operand:ident) => { 4 + 4 * $operand.len() as u32 };
+    (@size InlineI8 $operand:ident) => { 8 };
+    (@size InlineR $operand:ident) => { 8 };
+    (@size InlineNone $operand:ident) => { 0 };
+    (@size InlineVar $operand:ident) => { 2 };
+    (@size ShortInlineBrTarget $operand:ident) => { 1 };
+    (@size ShortInlineI $operand:ident) => { 1 };
+    (@size ShortInlineVar $operand:ident) => { 1 };
+    (@size $_other:ident $operand:ident) => { 4 };
+
+    (@operand InlineBrTarget $operand:ident) => { Operand::Target($operand) };
+    (@operand ShortInlineBrTarget $operand:ident) => { Operand::Target($operand) };
+    (@operand InlineSwitch $operand:ident) => { Operand::Switch($operand) };
+    (@operand InlineI $operand:ident) => { Operand::Int(*$operand as i64) };
+    (@operand ShortInlineI $operand:ident) => { Operand::Int(*$operand as i64) };
+    (@operand InlineI8 $operand:ident) => { Operand::Int(*$operand) };
+    (@operand InlineR $operand:ident) => { Operand::Float(*$operand) };
+    (@operand ShortInlineR $operand:ident) => { Operand::Float(*$operand as f64) };
+    (@operand InlineVar $operand:ident) => { Operand::Variable(*$operand) };
+    (@operand ShortInlineVar $operand:ident) => { Operand::Variable(*$operand as u16) };
+    (@operand InlineNone $operand:ident) => { Operand::None };
+    (@operand $_other:ident $operand:ident) => { Operand::Token(*$operand) };
+
+    (@targets InlineBrTarget $operand:ident) => { vec![$operand] };
+    (@targets ShortInlineBrTarget $operand:ident) => { vec![$operand] };
+    (@targets InlineSwitch $operand:ident) => { $operand.into_iter().collect() };
+    (@targets $_other:ident $operand:ident) => { Vec::new() };
 }
 
 opcodes!(
@@ -542,9 +867,13 @@ opcodes!(
     OPDEF(VOLATILE,"volatile.",Pop0,Push0,InlineNone,IPrefix,2,0xFE,0x13,Meta)
     OPDEF(TAILCALL,"tail.",Pop0,Push0,InlineNone,IPrefix,2,0xFE,0x14,Meta)
     OPDEF(INITOBJ,"initobj",PopI,Push0,InlineType,IObjModel,2,0xFE,0x15,Next)
+    OPDEF(CONSTRAINED,"constrained.",Pop0,Push0,InlineType,IPrefix,2,0xFE,0x16,Meta)
     OPDEF(CPBLK,"cpblk",PopIPopIPopI,Push0,InlineNone,IPrimitive,2,0xFE,0x17,Next)
     OPDEF(INITBLK,"initblk",PopIPopIPopI,Push0,InlineNone,IPrimitive,2,0xFE,0x18,Next)
+    OPDEF(NO,"no.",Pop0,Push0,ShortInlineI,IPrefix,2,0xFE,0x19,Meta)
+    OPDEF(RETHROW,"rethrow",Pop0,Push0,InlineNone,IObjModel,2,0xFE,0x1A,Throw)
     OPDEF(SIZEOF,"sizeof",Pop0,PushI,InlineType,IPrimitive,2,0xFE,0x1C,Next)
     OPDEF(REFANYTYPE,"refanytype",Pop1,PushI,InlineNone,IPrimitive,2,0xFE,0x1D,Next)
+    OPDEF(READONLY,"readonly.",Pop0,Push0,InlineNone,IPrefix,2,0xFE,0x1E,Meta)
 );
 
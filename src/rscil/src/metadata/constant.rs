@@ -0,0 +1,99 @@
+use super::*;
+
+const ELEMENT_TYPE_BOOLEAN: u8 = 0x02;
+const ELEMENT_TYPE_CHAR: u8 = 0x03;
+const ELEMENT_TYPE_I1: u8 = 0x04;
+const ELEMENT_TYPE_U1: u8 = 0x05;
+const ELEMENT_TYPE_I2: u8 = 0x06;
+const ELEMENT_TYPE_U2: u8 = 0x07;
+const ELEMENT_TYPE_I4: u8 = 0x08;
+const ELEMENT_TYPE_U4: u8 = 0x09;
+const ELEMENT_TYPE_I8: u8 = 0x0a;
+const ELEMENT_TYPE_U8: u8 = 0x0b;
+const ELEMENT_TYPE_R4: u8 = 0x0c;
+const ELEMENT_TYPE_R8: u8 = 0x0d;
+const ELEMENT_TYPE_STRING: u8 = 0x0e;
+const ELEMENT_TYPE_CLASS: u8 = 0x12;
+
+/// The decoded *Value* of a *Constant* row, see [`PeImage::constant_of`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstantValue {
+    Boolean(bool),
+    Char(u16),
+    I1(i8),
+    U1(u8),
+    I2(i16),
+    U2(u16),
+    I4(i32),
+    U4(u32),
+    I8(i64),
+    U8(u64),
+    R4(f32),
+    R8(f64),
+    String(String),
+    /// The **nullref** value of a field, parameter or property of a reference type.
+    NullRef,
+}
+
+impl ConstantValue {
+    /// # [II.22.9] Constant : 0x0B
+    /// [...]
+    ///
+    /// * *Type* (a 1-byte constant, followed by a 1-byte padding zero); see [§II.23.1.16]. The encoding of
+    ///   *Type* for the **nullref** value for FieldInit in ilasm ([§II.16.2]) is `ELEMENT_TYPE_CLASS` with a
+    ///   Value of a 4-byte zero.
+    ///
+    /// Decodes a *Value* blob holding a constant of the given element type. Strings are stored as UTF-16
+    /// without a length prefix or terminator, the blob's length gives theirs.
+    ///
+    /// [II.22.9]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=242
+    /// [§II.23.1.16]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=281
+    /// [§II.16.2]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=218
+    pub fn parse(element_type: u8, mut data: &[u8]) -> Result<ConstantValue, LushError> {
+        let value = match element_type {
+            ELEMENT_TYPE_BOOLEAN => ConstantValue::Boolean(data.read_u8().map_err(invalid)? != 0),
+            ELEMENT_TYPE_CHAR => ConstantValue::Char(data.read_u16::<LittleEndian>().map_err(invalid)?),
+            ELEMENT_TYPE_I1 => ConstantValue::I1(data.read_i8().map_err(invalid)?),
+            ELEMENT_TYPE_U1 => ConstantValue::U1(data.read_u8().map_err(invalid)?),
+            ELEMENT_TYPE_I2 => ConstantValue::I2(data.read_i16::<LittleEndian>().map_err(invalid)?),
+            ELEMENT_TYPE_U2 => ConstantValue::U2(data.read_u16::<LittleEndian>().map_err(invalid)?),
+            ELEMENT_TYPE_I4 => ConstantValue::I4(data.read_i32::<LittleEndian>().map_err(invalid)?),
+            ELEMENT_TYPE_U4 => ConstantValue::U4(data.read_u32::<LittleEndian>().map_err(invalid)?),
+            ELEMENT_TYPE_I8 => ConstantValue::I8(data.read_i64::<LittleEndian>().map_err(invalid)?),
+            ELEMENT_TYPE_U8 => ConstantValue::U8(data.read_u64::<LittleEndian>().map_err(invalid)?),
+            ELEMENT_TYPE_R4 => ConstantValue::R4(data.read_f32::<LittleEndian>().map_err(invalid)?),
+            ELEMENT_TYPE_R8 => ConstantValue::R8(data.read_f64::<LittleEndian>().map_err(invalid)?),
+            ELEMENT_TYPE_STRING => {
+                if !data.len().is_multiple_of(2) {
+                    return Err(LushError::InvalidBlob(format!("string constant has an odd length of {} bytes", data.len())));
+                }
+                let units: Vec<u16> = data.chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+                ConstantValue::String(String::from_utf16_lossy(&units))
+            }
+            ELEMENT_TYPE_CLASS => match data.read_u32::<LittleEndian>().map_err(invalid)? {
+                0 => ConstantValue::NullRef,
+                value => return Err(LushError::InvalidBlob(format!("class constant is 0x{:08X}, shall be a nullref", value))),
+            },
+            other => return Err(LushError::InvalidBlob(format!("invalid constant type 0x{:02X}", other))),
+        };
+        Ok(value)
+    }
+}
+
+impl PeImage {
+    /// Returns the compile-time constant of the field, parameter or property `parent`, e.g. the value of
+    /// an enum member or the default value of an optional parameter. `None` if it has no *Constant* row.
+    pub fn constant_of(&self, parent: CodedIndex) -> Result<Option<ConstantValue>, LushError> {
+        let Some(row) = parent.encode(CodedIndexTag::HasConstant)
+            .and_then(|key| self.rows_by_key::<Constant>(key).first().copied()) else {
+            return Ok(None);
+        };
+
+        let blob = self.get_blob(row.value).ok_or(LushError::InvalidSignature(row.value.0))?;
+        ConstantValue::parse(row.type_ as u8, blob).map(Some)
+    }
+}
+
+fn invalid(error: std::io::Error) -> LushError {
+    LushError::InvalidBlob(format!("invalid constant: {}", error))
+}
@@ -1,13 +1,13 @@
 
 pub(crate) type Buffer = Cursor<Vec<u8>>;
 
-use std::{cell::RefCell, collections::HashMap};
+use std::{collections::HashMap, sync::Mutex};
 
 use super::*;
 
 pub struct TableDecodeContext {
     row_count: HashMap<TableKind, u32>,
-    index_tracker: RefCell<HashMap<TableKind, u32>>,
+    index_tracker: Mutex<HashMap<TableKind, u32>>,
     pub heap_sizes: HeapSizes,
     coded_index_sizes: HashMap<CodedIndexTag, u8>,
 }
@@ -22,12 +22,12 @@ impl TableDecodeContext {
             row_count,
             heap_sizes,
             coded_index_sizes,
-            index_tracker: RefCell::new(HashMap::new()),
+            index_tracker: Mutex::new(HashMap::new()),
         }
     }
 
     pub fn get_index(&self, kind: TableKind) -> u32 {
-        let mut index_tracker = self.index_tracker.borrow_mut();
+        let mut index_tracker = self.index_tracker.lock().unwrap();
         let index = *index_tracker.get(&kind).unwrap_or(&1);
         index_tracker.insert(kind, index + 1);
         index
@@ -72,18 +72,33 @@ impl TableDecodeContext {
     pub fn get_coded_index_size(&self, tag: CodedIndexTag) -> u8 {
         *self.coded_index_sizes.get(&tag).unwrap_or(&0)
     }
+
+    /// Returns the offset of the first row of the given table, relative to the first row of the first
+    /// table in the #~ stream. Tables are stored in the order of their number, without any padding.
+    pub fn table_offset(&self, kind: TableKind) -> usize {
+        (0..u8::from(kind))
+            .filter_map(TableKind::try_from_u8)
+            .map(|table| self.get_row_count(table) as usize * table.row_size(self))
+            .sum()
+    }
 }
 
 pub trait TableDecode : Sized {
     type Output;
 
     fn decode(context: &TableDecodeContext, buffer: &mut Buffer) -> Result<Self::Output, std::io::Error>;
+
+    /// The number of bytes [`TableDecode::decode`] reads with the given context.
+    fn size(context: &TableDecodeContext) -> usize;
 }
 
 pub trait TableEnumDecode : Sized {
     type Output;
 
     fn decode(self, context: &TableDecodeContext, buffer: &mut Buffer) -> Result<Self::Output, std::io::Error>;
+
+    /// The number of bytes [`TableEnumDecode::decode`] reads with the given context.
+    fn size(self, context: &TableDecodeContext) -> usize;
 }
 
 impl TableDecode for u32 {
@@ -91,6 +106,10 @@ impl TableDecode for u32 {
     fn decode(_context: &TableDecodeContext, buffer: &mut Buffer) -> Result<Self, std::io::Error> {
         buffer.read_u32::<LittleEndian>()
     }
+
+    fn size(_context: &TableDecodeContext) -> usize {
+        std::mem::size_of::<Self>()
+    }
 }
 
 impl TableDecode for u16 {
@@ -98,6 +117,10 @@ impl TableDecode for u16 {
     fn decode(_context: &TableDecodeContext, buffer: &mut Buffer) -> Result<Self, std::io::Error> {
         buffer.read_u16::<LittleEndian>()
     }
+
+    fn size(_context: &TableDecodeContext) -> usize {
+        std::mem::size_of::<Self>()
+    }
 }
 
 impl TableDecode for u8 {
@@ -105,5 +128,58 @@ impl TableDecode for u8 {
     fn decode(_context: &TableDecodeContext, buffer: &mut Buffer) -> Result<Self, std::io::Error> {
         buffer.read_u8()
     }
+
+    fn size(_context: &TableDecodeContext) -> usize {
+        std::mem::size_of::<Self>()
+    }
 }
 
+
+/// # II.23.2 Blobs and signatures
+/// [...]
+///
+/// * If the value lies between 0 (0x00) and 127 (0x7F), inclusive, encode as a one-byte integer (bit 7 is clear, value held in bits 6 through 0)
+/// * If the value lies between 2<sup>8</sup> (0x80) and 2<sup>14</sup> – 1 (0x3FFF), inclusive, encode as a 2-byte integer with bit 15 set, bit 14 clear (value held in bits 13 through 0)
+/// * Otherwise, encode as a 4-byte integer, with bit 31 set, bit 30 set, bit 29 clear (value held in bits 28 through 0)
+///
+/// Returns the value and the number of bytes read from the buffer.
+pub fn read_compressed_u32(buffer: &mut impl Read) -> Result<(u32, u32), std::io::Error> {
+    let first = buffer.read_u8()? as u32;
+
+    if first & 0b1000_0000 == 0 {
+        Ok((first, 1))
+    } else if first & 0b1100_0000 == 0b1000_0000 {
+        Ok(((first & 0b0011_1111) << 8 | buffer.read_u8()? as u32, 2))
+    } else if first & 0b1110_0000 == 0b1100_0000 {
+        let mut bytes = [0u8; 3];
+        buffer.read_exact(&mut bytes)?;
+        Ok((u32::from_be_bytes([first as u8 & 0b0001_1111, bytes[0], bytes[1], bytes[2]]), 4))
+    } else {
+        Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Invalid compressed integer lead byte 0x{:02X}", first)))
+    }
+}
+
+/// # II.23.2 Blobs and signatures
+/// [...]
+///
+/// Signed integers are encoded as follows:
+///
+/// * If the value lies between -2<sup>6</sup> and 2<sup>6</sup>-1 inclusive: Represent the value as a 7-bit 2's complement number, giving 0x40 (-2<sup>6</sup>) to 0x3F (2<sup>6</sup>-1); Rotate this value 1 bit left, giving 0x01 (-2<sup>6</sup>) to 0x7E (2<sup>6</sup>-1); Encode as a one-byte integer, bit 7 clear, rotated value in bits 6 through 0, giving 0x01 (-2<sup>6</sup>) to 0x7E (2<sup>6</sup>-1).
+/// * If the value lies between -2<sup>13</sup> and 2<sup>13</sup>-1 inclusive: Represent the value as a 14-bit 2's complement number, giving 0x2000 (-2<sup>13</sup>) to 0x1FFF (2<sup>13</sup>-1); Rotate this value 1 bit left, giving 0x0001 (-2<sup>13</sup>) to 0x3FFE (2<sup>13</sup>-1); Encode as a two-byte integer: bit 15 set, bit 14 clear, rotated value in bits 13 through 0, giving 0x8001 (-2<sup>13</sup>) to 0xBFFE (2<sup>13</sup>-1).
+/// * If the value lies between -2<sup>28</sup> and 2<sup>28</sup>-1 inclusive: Represent the value as a 29-bit 2's complement representation, giving 0x10000000 (-2<sup>28</sup>) to 0xFFFFFFF (2<sup>28</sup>-1); Rotate this value 1-bit left, giving 0x00000001 (-2<sup>28</sup>) to 0x1FFFFFFE (2<sup>28</sup>-1); Encode as a four-byte integer: bit 31 set, 30 set, bit 29 clear, rotated value in bits 28 through 0, giving 0xC0000001 (-2<sup>28</sup>) to 0xDFFFFFFE (2<sup>28</sup>-1).
+///
+/// Returns the value and the number of bytes read from the buffer.
+pub fn read_compressed_i32(buffer: &mut impl Read) -> Result<(i32, u32), std::io::Error> {
+    let (rotated, bytes_read) = read_compressed_u32(buffer)?;
+    let sign_bits = match bytes_read {
+        1 => 0xFFFF_FFC0,
+        2 => 0xFFFF_E000,
+        _ => 0xF000_0000,
+    };
+
+    let value = match rotated & 1 {
+        0 => rotated >> 1,
+        _ => rotated >> 1 | sign_bits,
+    };
+    Ok((value as i32, bytes_read))
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use super::*;
+
+/// The differences between two images, see [`PeImage::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataDiff {
+    /// Tables with at least one added, removed or changed row, in table number order.
+    pub tables: Vec<TableDiff>,
+    /// The assembly versions (*MajorVersion*, *MinorVersion*, *BuildNumber*, *RevisionNumber*) of both
+    /// images, if they differ.
+    pub assembly_version: Option<([u16; 4], [u16; 4])>,
+    /// Whether the module version ids differ. The MVID changes on every build, so this is kept apart from
+    /// the other differences.
+    pub mvid_changed: bool,
+}
+
+impl MetadataDiff {
+    /// Whether the images differ in anything but their MVID.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty() && self.assembly_version.is_none()
+    }
+}
+
+/// The rows of a single table that differ between two images.
+///
+/// *TypeDef*, *MethodDef* and *Field* rows are keyed by their resolved names (`Namespace.Type`,
+/// `Namespace.Type::Member`) and compared by their flags and resolved base type or signature.
+///
+/// Rows of the other tables are compared by position: they're keyed by their row index (`[rid]`) and
+/// compared column by column, so a row inserted in the middle of a table shows up as every later row
+/// changing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableDiff {
+    pub kind: TableKind,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl PeImage {
+    /// Compares the metadata of `self` against `other`, reporting rows that were added in `other`, removed
+    /// from `self`, or changed between the two.
+    pub fn diff(&self, other: &PeImage) -> MetadataDiff {
+        let mut kinds: Vec<TableKind> = self.streams.metadata.present_tables().into_iter()
+            .chain(other.streams.metadata.present_tables())
+            .collect();
+        kinds.sort_by_key(|kind| u8::from(*kind));
+        kinds.dedup();
+
+        let tables = kinds.into_iter()
+            .filter_map(|kind| {
+                let old = self.keyed_rows(kind);
+                let new = other.keyed_rows(kind);
+
+                let mut added: Vec<String> = new.keys().filter(|key| !old.contains_key(*key)).cloned().collect();
+                let mut removed: Vec<String> = old.keys().filter(|key| !new.contains_key(*key)).cloned().collect();
+                let mut changed: Vec<String> = old.iter()
+                    .filter(|(key, value)| new.get(*key).is_some_and(|other| other != *value))
+                    .map(|(key, _)| key.clone())
+                    .collect();
+
+                if added.is_empty() && removed.is_empty() && changed.is_empty() {
+                    return None;
+                }
+
+                added.sort();
+                removed.sort();
+                changed.sort();
+                Some(TableDiff { kind, added, removed, changed })
+            })
+            .collect();
+
+        let version = |image: &PeImage| image.get_assembly()
+            .map(|assembly| [assembly.major_version, assembly.minor_version, assembly.build_number, assembly.revision_number]);
+        let assembly_version = match (version(self), version(other)) {
+            (Some(old), Some(new)) if old != new => Some((old, new)),
+            _ => None,
+        };
+
+        MetadataDiff {
+            tables,
+            assembly_version,
+            mvid_changed: self.mvid() != other.mvid(),
+        }
+    }
+
+    /// Maps a key identifying each row of the table to a rendering of the row that is comparable across images.
+    fn keyed_rows(&self, kind: TableKind) -> HashMap<String, String> {
+        let mut rows = HashMap::new();
+        let Some(table) = self.streams.metadata.try_get_table(kind) else {
+            return rows;
+        };
+
+        match kind {
+            TableKind::TypeDef => {
+                for type_def in self.table::<TypeDef>() {
+                    let extends = match type_def.extends.index {
+                        0 => String::new(),
+                        _ => self.coded_type_name(type_def.extends),
+                    };
+                    insert_unique(&mut rows, self.full_type_name(type_def.type_namespace, type_def.type_name),
+                        format!("{:?} {}", type_def.flags, extends));
+                }
+            },
+            TableKind::MethodDef => {
+                let owners = self.owning_types(|type_def| type_def.method_list, self.table::<MethodDef>().len());
+                for method in self.table::<MethodDef>() {
+                    let key = format!("{}::{}", owners[method.index as usize - 1], self.get_string(method.name));
+                    let signature = match self.get_method_signature(method) {
+                        Ok(signature) => self.method_signature_name(&signature),
+                        Err(_) => format!("{:?}", self.get_blob(method.signature)),
+                    };
+                    insert_unique(&mut rows, key, format!("{:?} {:?} {}", method.flags, method.impl_flags, signature));
+                }
+            },
+            TableKind::Field => {
+                let owners = self.owning_types(|type_def| type_def.field_list, self.table::<Field>().len());
+                for field in self.table::<Field>() {
+                    let key = format!("{}::{}", owners[field.index as usize - 1], self.get_string(field.name));
+                    let signature = match self.get_field_signature(field) {
+                        Ok(signature) => self.field_signature_name(&signature),
+                        Err(_) => format!("{:?}", self.get_blob(field.signature)),
+                    };
+                    insert_unique(&mut rows, key, format!("{:?} {}", field.flags, signature));
+                }
+            },
+            // Without a resolved name to key them by, rows are compared by position.
+            _ => {
+                for row in table {
+                    let columns = row.columns(self)
+                        .into_iter()
+                        .map(|(name, value)| format!("{}: {}", name, value))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    rows.insert(format!("[{}]", row.index()), columns);
+                }
+            },
+        }
+
+        rows
+    }
+
+    /// Resolves the name of the *TypeDef* owning each row of a list run (*MethodList*, *FieldList*),
+    /// indexed by row index - 1.
+    fn owning_types(&self, list: impl Fn(&TypeDef) -> CodedIndex, count: usize) -> Vec<String> {
+        let mut owners = vec![String::new(); count];
+
+        for type_def in self.table::<TypeDef>() {
+            let name = self.full_type_name(type_def.type_namespace, type_def.type_name);
+            for rid in self.list_run(type_def, &list) {
+                if let Some(owner) = (rid as usize).checked_sub(1).and_then(|i| owners.get_mut(i)) {
+                    *owner = name.clone();
+                }
+            }
+        }
+        owners
+    }
+
+    /// Renders a method signature with resolved type names, so that it doesn't depend on the row indices
+    /// of the types it references, e.g. `Default instance System.Void (System.String[])`.
+    fn method_signature_name(&self, signature: &MethodSignature) -> String {
+        let params: Vec<String> = signature.params.iter().map(|param| self.sig_type_name(param, None)).collect();
+        let this = match (signature.has_this, signature.explicit_this) {
+            (true, true) => " instance explicit",
+            (true, false) => " instance",
+            _ => "",
+        };
+        let generics = match signature.generic_param_count {
+            0 => String::new(),
+            count => format!("<{}>", count),
+        };
+        format!("{:?}{} {}{} ({})", signature.calling_convention, this,
+            self.sig_type_name(&signature.return_type, None), generics, params.join(", "))
+    }
+
+    /// Renders a field signature with resolved type names, see [`PeImage::method_signature_name`].
+    fn field_signature_name(&self, signature: &FieldSignature) -> String {
+        let mut name = self.sig_type_name(&signature.field_type, None);
+        for modifier in &signature.custom_modifiers {
+            let kind = if modifier.required { "modreq" } else { "modopt" };
+            name.push_str(&format!(" {}({})", kind, self.coded_type_name(modifier.type_ref)));
+        }
+        name
+    }
+
+    fn coded_type_name(&self, index: CodedIndex) -> String {
+        self.type_name(index)
+            .unwrap_or_else(|| format!("{}[{}]", index.table.name(), index.index))
+    }
+}
+
+/// Inserts `value` under `key`, disambiguating overloads and duplicate names with a `#n` suffix.
+fn insert_unique(rows: &mut HashMap<String, String>, key: String, value: String) {
+    let mut unique = key.clone();
+    let mut n = 1;
+    while rows.contains_key(&unique) {
+        n += 1;
+        unique = format!("{}#{}", key, n);
+    }
+    rows.insert(unique, value);
+}
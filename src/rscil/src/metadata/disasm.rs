@@ -0,0 +1,106 @@
+use std::fmt::Write;
+
+use super::*;
+
+impl MethodBody {
+    /// Renders the method body as ilasm-style text, one instruction per line:
+    ///
+    /// ```text
+    /// IL_0000: ldstr "Hello World"
+    /// IL_0005: call System.Console::WriteLine
+    /// IL_000a: ret
+    /// ```
+    ///
+    /// Tokens are resolved to names through the heaps and tables of `image`, branch targets are rendered
+    /// as `IL_xxxx` labels.
+    pub fn disassemble(&self, image: &PeImage) -> String {
+        let mut text = String::new();
+
+        for instruction in &self.body {
+            let name = instruction.opcode.code().metadata().name;
+            write!(text, "{}: {}", label(instruction.offset), name).unwrap();
+
+            match instruction.opcode.operand() {
+                Operand::None => {}
+                Operand::Int(value) => write!(text, " {}", value).unwrap(),
+                Operand::Float(value) => write!(text, " {}", value).unwrap(),
+                Operand::Variable(number) => write!(text, " {}", number).unwrap(),
+                Operand::Token(token) => write!(text, " {}", image.format_token(token)).unwrap(),
+                Operand::Target(target) => write!(text, " {}", label(target.offset)).unwrap(),
+                Operand::Switch(targets) => {
+                    let labels: Vec<String> = targets.iter().map(|target| label(target.offset)).collect();
+                    write!(text, " ({})", labels.join(", ")).unwrap();
+                }
+            }
+            text.push('\n');
+        }
+
+        text
+    }
+}
+
+impl PeImage {
+    /// Renders an in-line token: user strings as quoted literals, types by their full name and members as
+    /// `Type::Name`, followed by the type arguments of an instantiated generic method. Tokens that can't be
+    /// resolved are rendered as their raw value.
+    fn format_token(&self, token: MetadataToken) -> String {
+        let name = match token {
+            MetadataToken::UserString(_) => self.get_user_string(token).map(|string| quote(&string)),
+            MetadataToken::Table(table @ (TableKind::TypeDef | TableKind::TypeRef), rid) => self.type_name(CodedIndex::from(table, rid)),
+            MetadataToken::Table(TableKind::MethodDef, rid) => self.get_method_def(rid).map(|method_def| {
+                self.qualify(self.method_owner(rid), method_def.name)
+            }),
+            MetadataToken::Table(TableKind::Field, rid) => self.get_field(rid).map(|field| {
+                self.qualify(self.field_owner(rid), field.name)
+            }),
+            MetadataToken::Table(TableKind::MemberRef, rid) => self.get_member_ref(rid).map(|member_ref| {
+                let info = self.member_ref_info(member_ref);
+                match info.declaring_type {
+                    Some(declaring_type) => format!("{}::{}", declaring_type, info.name),
+                    None => info.name,
+                }
+            }),
+            MetadataToken::Table(TableKind::MethodSpec, rid) => self.table::<MethodSpec>().get(rid).map(|method_spec| {
+                let method = self.format_token(MetadataToken::Table(method_spec.method.table, method_spec.method.index));
+                match self.get_method_spec(method_spec) {
+                    Ok(signature) => {
+                        let args: Vec<String> = signature.args.iter().map(|arg| self.sig_type_name(arg, None)).collect();
+                        format!("{}<{}>", method, args.join(","))
+                    }
+                    Err(_) => method,
+                }
+            }),
+            _ => None,
+        };
+
+        name.unwrap_or_else(|| token.to_string())
+    }
+
+    fn qualify(&self, declaring_type: Option<&TypeDef>, name: StringIndex) -> String {
+        match declaring_type {
+            Some(type_def) => format!("{}::{}", self.full_type_name(type_def.type_namespace, type_def.type_name), self.get_string(name)),
+            None => self.get_string(name).to_string(),
+        }
+    }
+}
+
+fn label(offset: u32) -> String {
+    format!("IL_{:04x}", offset)
+}
+
+fn quote(string: &str) -> String {
+    let mut quoted = String::with_capacity(string.len() + 2);
+    quoted.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            char => quoted.push(char),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+/// Renders a decoded column value for [`PeImage::dump_tables`].
+pub(crate) trait DisplayColumn {
+    fn display(&self, image: &PeImage) -> String;
+}
+
+macro_rules! display_column_hex {
+    ($($ty:ty),*) => {
+        $(
+            impl DisplayColumn for $ty {
+                fn display(&self, _: &PeImage) -> String {
+                    format!("0x{:0width$X}", self, width = std::mem::size_of::<$ty>() * 2)
+                }
+            }
+        )*
+    };
+}
+
+display_column_hex!(u8, u16, u32);
+
+impl DisplayColumn for StringIndex {
+    fn display(&self, image: &PeImage) -> String {
+        match image.streams.strings.get(self.0) {
+            Some(string) => format!("{:?}", string),
+            None => format!("#Strings[0x{:X}]", self.0),
+        }
+    }
+}
+
+impl DisplayColumn for BlobIndex {
+    fn display(&self, _: &PeImage) -> String {
+        format!("#Blob[0x{:X}]", self.0)
+    }
+}
+
+impl DisplayColumn for GuidIndex {
+    fn display(&self, _: &PeImage) -> String {
+        format!("#GUID[{}]", self.0)
+    }
+}
+
+impl DisplayColumn for CodedIndex {
+    fn display(&self, _: &PeImage) -> String {
+        format!("{}[{}]", self.table.name(), self.index)
+    }
+}
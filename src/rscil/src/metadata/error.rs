@@ -0,0 +1,91 @@
+use std::fmt;
+
+/// Errors that can occur while reading or interpreting a PE image.
+#[derive(Debug)]
+pub enum LushError {
+    Io(std::io::Error),
+    /// The image ended in the middle of a structure.
+    UnexpectedEof,
+    /// A table number that doesn't correspond to any [`TableKind`](super::TableKind).
+    InvalidTableKind(u8),
+    /// A metadata token that couldn't be parsed from its textual form, or doesn't reference the kind of row
+    /// expected, see [`MetadataToken`](super::MetadataToken).
+    InvalidToken(String),
+    /// A coded index whose tag is reserved or unused for its [`CodedIndexTag`](super::CodedIndexTag).
+    InvalidCodedIndexTag { tag: super::CodedIndexTag, value: u8 },
+    /// The signature blob at the given offset into the #Blob heap couldn't be decoded.
+    InvalidSignature(u32),
+    /// A blob (signature, marshalling descriptor, ...) that couldn't be decoded.
+    InvalidBlob(String),
+    /// An opcode that isn't defined by Partition VI.C.2, given by its two bytes as in
+    /// [`Code::from`](super::Code::from) and the IL offset of the instruction.
+    InvalidOpcode { bytes: [u8; 2], offset: u32 },
+    /// The method has no CIL body, its *RVA* is 0.
+    NoMethodBody(super::MetadataToken),
+    /// The metadata has no "#~" (or "#-") tables stream, without which nothing can be read.
+    MissingStream(&'static str),
+    /// A #~ stream version other than 2.0.
+    UnsupportedVersion { major: u8, minor: u8 },
+    /// The image violates a rule of the spec, only reported when parsing with [`ParseOptions::strict`](super::ParseOptions::strict).
+    SpecViolation(String),
+    /// Processing a file panicked, with the panic message. Only reported by `scan_directory`, which keeps
+    /// scanning the other files.
+    Panic(String),
+}
+
+/// Shorthand for results of the crate, the error type defaults to [`LushError`].
+pub type Result<T, E = LushError> = std::result::Result<T, E>;
+
+impl fmt::Display for LushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LushError::Io(error) => write!(f, "{}", error),
+            LushError::UnexpectedEof => write!(f, "Unexpected end of image"),
+            LushError::InvalidTableKind(value) => write!(f, "Invalid table kind: 0x{:02X}", value),
+            LushError::InvalidToken(token) => write!(f, "Invalid metadata token: {:?}", token),
+            LushError::InvalidCodedIndexTag { tag, value } => write!(f, "Invalid {:?} coded index tag: {}", tag, value),
+            LushError::InvalidSignature(index) => write!(f, "Invalid signature at #Blob[0x{:X}]", index),
+            LushError::InvalidBlob(reason) => write!(f, "Invalid blob: {}", reason),
+            LushError::InvalidOpcode { bytes: [op1, op2], offset } => write!(f, "Invalid opcode 0x{:02X} 0x{:02X} at IL_{:04x}", op1, op2, offset),
+            LushError::NoMethodBody(token) => write!(f, "Method {} has no body", token),
+            LushError::MissingStream(name) => write!(f, "Missing {} stream", name),
+            LushError::UnsupportedVersion { major, minor } => write!(f, "Unsupported #~ stream version: {}.{}", major, minor),
+            LushError::SpecViolation(violation) => write!(f, "Spec violation: {}", violation),
+            LushError::Panic(message) => write!(f, "Panicked: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for LushError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LushError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LushError {
+    fn from(error: std::io::Error) -> Self {
+        // Unwrap a LushError that was propagated through a function returning std::io::Error.
+        if error.get_ref().is_some_and(|inner| inner.is::<LushError>()) {
+            return *error.into_inner().unwrap().downcast::<LushError>().unwrap();
+        }
+
+        match error.kind() {
+            std::io::ErrorKind::UnexpectedEof => LushError::UnexpectedEof,
+            _ => LushError::Io(error),
+        }
+    }
+}
+
+/// Lets functions that still return [`std::io::Error`] propagate a [`LushError`] with `?`.
+impl From<LushError> for std::io::Error {
+    fn from(error: LushError) -> Self {
+        match error {
+            LushError::Io(error) => error,
+            LushError::UnexpectedEof => std::io::Error::new(std::io::ErrorKind::UnexpectedEof, error),
+            error => std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+        }
+    }
+}
@@ -42,6 +42,45 @@ macro_rules! flag {
             pub fn check_flag(&self, flag: $size) -> bool {
                 self.0 & flag == flag
             }
+
+            /// Returns the names of the declared flags that are set, in declaration order.
+            ///
+            /// A `*_MASK` constant starts a group of the values declared after it that fit in the mask, and
+            /// a group contributes only the value equal to the masked bits, e.g. only `PUBLIC` rather than
+            /// `FAMILY` and `FAM_AND_ASSEM` too. Masks themselves are never listed. Should none of the
+            /// group's values match exactly, its nonzero values are tested bit by bit instead.
+            pub fn flag_names(&self) -> Vec<&'static str> {
+                const FLAGS: &[(&str, $size)] = &[$((stringify!($flag), $value)),*];
+
+                let mut names = Vec::new();
+                let mut index = 0;
+                while index < FLAGS.len() {
+                    let (name, value) = FLAGS[index];
+                    index += 1;
+
+                    if !name.ends_with("_MASK") {
+                        if value != 0 && self.0 & value == value {
+                            names.push(name);
+                        }
+                        continue;
+                    }
+
+                    let mask = value;
+                    let start = index;
+                    while index < FLAGS.len() && !FLAGS[index].0.ends_with("_MASK") && FLAGS[index].1 & !mask == 0 {
+                        index += 1;
+                    }
+                    let group = &FLAGS[start..index];
+
+                    match group.iter().find(|(_, value)| self.0 & mask == *value) {
+                        Some((name, _)) => names.push(*name),
+                        None => names.extend(group.iter()
+                            .filter(|(_, value)| *value != 0 && self.0 & value == *value)
+                            .map(|(name, _)| *name)),
+                    }
+                }
+                names
+            }
         }
 
         impl std::ops::BitAnd for $name {
@@ -52,6 +91,14 @@ macro_rules! flag {
             }
         }
 
+        impl std::ops::BitOr for $name {
+            type Output = Self;
+
+            fn bitor(self, rhs: Self) -> Self {
+                Self(self.0 | rhs.0)
+            }
+        }
+
         impl From<$size> for $name {
             fn from(value: $size) -> Self {
                 $name(value)
@@ -64,6 +111,25 @@ macro_rules! flag {
             }
         }
 
+        impl DisplayColumn for $name {
+            fn display(&self, _: &PeImage) -> String {
+                format!("0x{:0width$X}", self.0, width = std::mem::size_of::<$size>() * 2)
+            }
+        }
+
+        /// Serializes the flags as their numeric `value` and their `names`, see `flag_names`.
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                use serde::ser::SerializeStruct;
+
+                let mut state = serializer.serialize_struct(stringify!($name), 2)?;
+                state.serialize_field("value", &self.0)?;
+                state.serialize_field("names", &self.flag_names())?;
+                state.end()
+            }
+        }
+
         impl TableDecode for $name {
             type Output = Self;
         
@@ -73,6 +139,10 @@ macro_rules! flag {
                     Ok(Self::new(value))
                 }
             }
+
+            fn size(_: &TableDecodeContext) -> usize {
+                std::mem::size_of::<$size>()
+            }
         }
     };
     (@read $buffer:ident u8) => { $buffer.read_u8()? };
@@ -89,8 +159,11 @@ flag!(
     /// | DisableJITcompileOptimizer | `0x4000` | Reserved (a conforming implementation of the CLI can ignore this setting on read; some implementations might use this bit to indicate that a CIL-to-native-code compiler should not generate optimized code) |
     /// | EnableJITcompileTracking   | `0x8000` | Reserved  (a conforming implementation of the CLI can ignore this setting on read; some implementations might use this bit to indicate that a CIL-to-native-code compiler should generate CIL-to-native code map) |
     /// 
+    /// The *Flags* column of [`Assembly`] and [`AssemblyRef`] holding these is 4 bytes wide, so bits above
+    /// `0xFFFF` are kept even though none are defined.
+    /// 
     /// [II.23.1.2]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=275
-    AssemblyFlags {
+    AssemblyFlags : u32 {
         PUBLIC_KEY = 0x0001,
         RETARGETABLE = 0x0100,
         DISABLE_JIT_COMPILE_OPTIMIZER = 0x4000,
@@ -162,6 +235,13 @@ flag!(
     }
 );
 
+impl FieldAttributes {
+    /// The value under [`FieldAttributes::FIELD_ACCESS_MASK`].
+    pub fn access(&self) -> MemberAccess {
+        MemberAccess::from((self.0 & FieldAttributes::FIELD_ACCESS_MASK.0) as u8)
+    }
+}
+
 flag!(
     /// [II.23.1.6] Flags for files [FileAttributes]
     /// 
@@ -243,6 +323,53 @@ flag!(
     }
 );
 
+impl PInvokeAttributes {
+    /// The value under [`PInvokeAttributes::CHAR_SET_MASK`].
+    pub fn char_set(&self) -> CharSet {
+        match self.0 & PInvokeAttributes::CHAR_SET_MASK.0 {
+            0x0000 => CharSet::NotSpec,
+            0x0002 => CharSet::Ansi,
+            0x0004 => CharSet::Unicode,
+            _ => CharSet::Auto,
+        }
+    }
+
+    /// The value under [`PInvokeAttributes::CALL_CONV_MASK`].
+    pub fn call_conv(&self) -> CallConv {
+        match self.0 & PInvokeAttributes::CALL_CONV_MASK.0 {
+            0x0100 => CallConv::PlatformApi,
+            0x0200 => CallConv::Cdecl,
+            0x0300 => CallConv::Stdcall,
+            0x0400 => CallConv::Thiscall,
+            0x0500 => CallConv::Fastcall,
+            _ => CallConv::Invalid,
+        }
+    }
+}
+
+/// How strings are marshalled to a P/Invoke target, see [`PInvokeAttributes::char_set`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharSet {
+    NotSpec,
+    Ansi,
+    Unicode,
+    /// Ansi or Unicode, whichever the platform prefers.
+    Auto,
+}
+
+/// The unmanaged calling convention of a P/Invoke target, see [`PInvokeAttributes::call_conv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallConv {
+    /// The default convention of the platform, `winapi` in ilasm.
+    PlatformApi,
+    Cdecl,
+    Stdcall,
+    Thiscall,
+    Fastcall,
+    /// Zero or one of the unused values `0x0600` and `0x0700`.
+    Invalid,
+}
+
 flag!(
     /// [II.23.1.9] Flags for ManifestResource [ManifestResourceAttributes] 
     ///
@@ -319,6 +446,50 @@ flag!(
     }
 );
 
+impl MethodAttributes {
+    /// The value under [`MethodAttributes::MEMBER_ACCESS_MASK`].
+    pub fn access(&self) -> MemberAccess {
+        MemberAccess::from((self.0 & MethodAttributes::MEMBER_ACCESS_MASK.0) as u8)
+    }
+}
+
+/// The accessibility of a field or method, see [`FieldAttributes::access`] and [`MethodAttributes::access`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberAccess {
+    CompilerControlled,
+    Private,
+    FamAndAssem,
+    Assembly,
+    Family,
+    FamOrAssem,
+    Public,
+    /// The unused value `0x7`.
+    Invalid,
+}
+
+impl MemberAccess {
+    /// Whether the member can be accessed from outside its assembly, that is `public`, `protected` or
+    /// `protected internal`.
+    pub fn is_visible_outside_assembly(&self) -> bool {
+        matches!(self, MemberAccess::Public | MemberAccess::Family | MemberAccess::FamOrAssem)
+    }
+}
+
+impl From<u8> for MemberAccess {
+    fn from(value: u8) -> Self {
+        match value {
+            0x0 => MemberAccess::CompilerControlled,
+            0x1 => MemberAccess::Private,
+            0x2 => MemberAccess::FamAndAssem,
+            0x3 => MemberAccess::Assembly,
+            0x4 => MemberAccess::Family,
+            0x5 => MemberAccess::FamOrAssem,
+            0x6 => MemberAccess::Public,
+            _ => MemberAccess::Invalid,
+        }
+    }
+}
+
 flag!(
     /// # [II.23.1.11] Flags for methods [MethodImplAttributes] 
     /// | Flag                            | Value    | Description |
@@ -360,6 +531,31 @@ flag!(
     }
 );
 
+impl MethodImplAttributes {
+    /// The value under [`MethodImplAttributes::CODE_TYPE_MASK`].
+    pub fn code_type(&self) -> MethodCodeType {
+        match self.0 & MethodImplAttributes::CODE_TYPE_MASK.0 {
+            0x0 => MethodCodeType::IL,
+            0x1 => MethodCodeType::Native,
+            0x2 => MethodCodeType::OPTIL,
+            _ => MethodCodeType::Runtime,
+        }
+    }
+}
+
+/// How a method is implemented, see [`MethodImplAttributes::code_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodCodeType {
+    /// The method body is CIL.
+    IL,
+    /// The method body is native code.
+    Native,
+    /// Reserved, shall be zero in conforming implementations.
+    OPTIL,
+    /// The method implementation is provided by the runtime.
+    Runtime,
+}
+
 flag!(
     /// # [II.23.1.12] Flags for MethodSemantics [MethodSemanticsAttributes] 
     /// 
@@ -507,6 +703,66 @@ flag!(
     }
 );
 
+impl TypeAttributes {
+    /// The value under [`TypeAttributes::VISIBILITY_MASK`].
+    pub fn visibility(&self) -> TypeVisibility {
+        match self.0 & TypeAttributes::VISIBILITY_MASK.0 {
+            0x0 => TypeVisibility::NotPublic,
+            0x1 => TypeVisibility::Public,
+            0x2 => TypeVisibility::NestedPublic,
+            0x3 => TypeVisibility::NestedPrivate,
+            0x4 => TypeVisibility::NestedFamily,
+            0x5 => TypeVisibility::NestedAssembly,
+            0x6 => TypeVisibility::NestedFamAndAssem,
+            _ => TypeVisibility::NestedFamOrAssem,
+        }
+    }
+
+    /// The value under [`TypeAttributes::LAYOUT_MASK`], `None` for the invalid value `0x18`.
+    pub fn layout(&self) -> Option<TypeLayout> {
+        match self.0 & TypeAttributes::LAYOUT_MASK.0 {
+            0x00 => Some(TypeLayout::Auto),
+            0x08 => Some(TypeLayout::Sequential),
+            0x10 => Some(TypeLayout::Explicit),
+            _ => None,
+        }
+    }
+
+    /// Whether the value under [`TypeAttributes::CLASS_SEMANTICS_MASK`] is [`TypeAttributes::INTERFACE`].
+    pub fn is_interface(&self) -> bool {
+        self.0 & TypeAttributes::CLASS_SEMANTICS_MASK.0 == TypeAttributes::INTERFACE.0
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.contains(TypeAttributes::ABSTRACT)
+    }
+
+    pub fn is_sealed(&self) -> bool {
+        self.contains(TypeAttributes::SEALED)
+    }
+}
+
+/// The visibility of a type, see [`TypeAttributes::visibility`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeVisibility {
+    NotPublic,
+    Public,
+    NestedPublic,
+    NestedPrivate,
+    NestedFamily,
+    NestedAssembly,
+    NestedFamAndAssem,
+    NestedFamOrAssem,
+}
+
+/// The field layout of a type, see [`TypeAttributes::layout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeLayout {
+    Auto,
+    Sequential,
+    Explicit,
+}
+
 flag!(
     /// # [II.24.2.6] #~ stream 
     /// [...]
@@ -521,14 +777,73 @@ flag!(
     /// | `0x02`         | Size of "#GUID" stream >= 216. |
     /// | `0x04`         | Size of "#Blob" stream >= 216. |
     /// 
+    /// The standard leaves the other bits reserved, but the uncompressed "#-" streams written for
+    /// edit-and-continue use a few more of them:
+    /// 
+    /// | Heap size flag | Description | 
+    /// | -------------- | ----------- |
+    /// | `0x20`         | The metadata only holds the deltas of an edit. |
+    /// | `0x40`         | A 4-byte *ExtraData* value follows the *Rows* array, see [`MetadataStream::extra_data`]. |
+    /// | `0x80`         | The metadata may contain deleted rows. |
+    /// 
     /// [II.24.2.6]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=299
     HeapSizes : u8 {
         STRING_FLAG = 0x01,
         GUID_FLAG = 0x02,
         BLOB_FLAG = 0x04,
+        DELTA_ONLY = 0x20,
+        EXTRA_DATA = 0x40,
+        HAS_DELETES = 0x80,
     }
 );
 
+impl HeapSizes {
+    /// Whether the "#Strings" heap is indexed with 4 bytes.
+    pub fn is_large_strings(&self) -> bool {
+        self.contains(HeapSizes::STRING_FLAG)
+    }
+
+    /// Whether the "#Blob" heap is indexed with 4 bytes.
+    pub fn is_large_blob(&self) -> bool {
+        self.contains(HeapSizes::BLOB_FLAG)
+    }
+
+    /// Whether the "#GUID" heap is indexed with 4 bytes.
+    pub fn is_large_guid(&self) -> bool {
+        self.contains(HeapSizes::GUID_FLAG)
+    }
+
+    /// Whether the metadata only holds the deltas of an edit-and-continue session.
+    pub fn is_delta_only(&self) -> bool {
+        self.contains(HeapSizes::DELTA_ONLY)
+    }
+
+    /// Whether the *Rows* array is followed by a 4-byte *ExtraData* value.
+    pub fn has_extra_data(&self) -> bool {
+        self.contains(HeapSizes::EXTRA_DATA)
+    }
+
+    /// Whether the metadata may contain rows deleted by edit-and-continue.
+    pub fn has_deletes(&self) -> bool {
+        self.contains(HeapSizes::HAS_DELETES)
+    }
+
+    /// Width in bytes of an index into the "#Strings" heap.
+    pub fn string_index_size(&self) -> u8 {
+        if self.is_large_strings() { 4 } else { 2 }
+    }
+
+    /// Width in bytes of an index into the "#Blob" heap.
+    pub fn blob_index_size(&self) -> u8 {
+        if self.is_large_blob() { 4 } else { 2 }
+    }
+
+    /// Width in bytes of an index into the "#GUID" heap.
+    pub fn guid_index_size(&self) -> u8 {
+        if self.is_large_guid() { 4 } else { 2 }
+    }
+}
+
 flag!(
     /// # [II.25.2.2.1] Characteristics
     /// 
@@ -552,6 +867,12 @@ flag!(
     }
 );
 
+impl FileCharacteristics {
+    pub fn is_dll(&self) -> bool {
+        self.contains(FileCharacteristics::IMAGE_FILE_DLL)
+    }
+}
+
 flag!(
     /// # [II.25.3] Section headers 
     /// 
@@ -604,6 +925,27 @@ flag!(
     }
 );
 
+flag!(
+    /// # [II.25.3.3.3] Vtable fixup 
+    /// 
+    /// [...]
+    /// 
+    /// | Constant                         | Value  | Description |
+    /// | -------------------------------- | ------ | ----------- |
+    /// | `COR_VTABLE_32BIT`               | `0x01` | Vtable slots are 32 bits. |
+    /// | `COR_VTABLE_64BIT`               | `0x02` | Vtable slots are 64 bits. |
+    /// | `COR_VTABLE_FROM_UNMANAGED`      | `0x04` | Transition from unmanaged to managed code. |
+    /// | `COR_VTABLE_CALL_MOST_DERIVED`   | `0x10` | Call most derived method described by the token (only valid for virtual methods). |
+    /// 
+    /// [II.25.3.3.3]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=310
+    VTableFixupFlags {
+        COR_VTABLE_32BIT = 0x01,
+        COR_VTABLE_64BIT = 0x02,
+        COR_VTABLE_FROM_UNMANAGED = 0x04,
+        COR_VTABLE_CALL_MOST_DERIVED = 0x10,
+    }
+);
+
 flag!(
     /// # [II.25.4.1] Method header type values 
     ///
@@ -640,11 +982,40 @@ flag!(
 );
 
 impl MethodHeaderType {
+    /// The format is stored in the two least significant bits, which are `0b11` for fat headers, so a plain
+    /// [`MethodHeaderType::contains`] check would also match fat headers.
     pub fn is_tiny_format(&self) -> bool {
-        self.contains(MethodHeaderType::COR_IL_METHOD_TINY_FORMAT)
+        self.0 & 0x3 == MethodHeaderType::COR_IL_METHOD_TINY_FORMAT.0
     }
 
     pub fn is_fat_format(&self) -> bool {
-        self.contains(MethodHeaderType::COR_IL_METHOD_FAT_FORMAT)
+        self.0 & 0x3 == MethodHeaderType::COR_IL_METHOD_FAT_FORMAT.0
+    }
+
+    pub fn has_more_sections(&self) -> bool {
+        self.contains(MethodHeaderType::COR_IL_METHOD_MORE_SECTS)
+    }
+}
+
+flag!(
+    /// # [II.25.4.5] Method data section 
+    /// 
+    /// [...]
+    /// 
+    /// The first byte of the header is the **Kind** of the section, made up of the following flags: 
+    /// 
+    /// | Flag                         | Value  | Description |
+    /// | ---------------------------- | ------ | ----------- |
+    /// | `CorILMethod_Sect_EHTable`   | `0x1`  | Exception handling data. |
+    /// | `CorILMethod_Sect_OptILTable`| `0x2`  | Reserved, shall be 0. |
+    /// | `CorILMethod_Sect_FatFormat` | `0x40` | Data format is of the fat variety, meaning there is a 3-byte length least-significant byte first format. If not set, the header is small with a 1-byte length |
+    /// | `CorILMethod_Sect_MoreSects` | `0x80` | Another data section occurs after this current section |
+    /// 
+    /// [II.25.4.5]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=312
+    MethodSectionFlags : u8 {
+        COR_IL_METHOD_SECT_EH_TABLE = 0x1,
+        COR_IL_METHOD_SECT_OPT_IL_TABLE = 0x2,
+        COR_IL_METHOD_SECT_FAT_FORMAT = 0x40,
+        COR_IL_METHOD_SECT_MORE_SECTS = 0x80,
     }
-}
\ No newline at end of file
+);
\ No newline at end of file
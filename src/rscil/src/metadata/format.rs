@@ -0,0 +1,133 @@
+use super::*;
+
+/// The C# keywords of the built-in types, by the full name of the type they alias.
+const KEYWORDS: [(&str, &str); 17] = [
+    ("System.Void", "void"),
+    ("System.Boolean", "bool"),
+    ("System.Char", "char"),
+    ("System.SByte", "sbyte"),
+    ("System.Byte", "byte"),
+    ("System.Int16", "short"),
+    ("System.UInt16", "ushort"),
+    ("System.Int32", "int"),
+    ("System.UInt32", "uint"),
+    ("System.Int64", "long"),
+    ("System.UInt64", "ulong"),
+    ("System.Single", "float"),
+    ("System.Double", "double"),
+    ("System.IntPtr", "nint"),
+    ("System.UIntPtr", "nuint"),
+    ("System.String", "string"),
+    ("System.Object", "object"),
+];
+
+/// The required modifier the C# compiler marks `in` parameters with.
+const IN_ATTRIBUTE: &str = "System.Runtime.InteropServices.InAttribute";
+
+impl PeImage {
+    /// Renders a signature type the way C# spells it, e.g. `string`, `int[]`, `List<string>`, `in T` or
+    /// `Span<byte>*`: built-in types by their keyword, and other types by their name without namespace
+    /// or generic arity suffix, nested types as `Outer.Inner`.
+    ///
+    /// Generic parameters are rendered as `!n` and `!!n` like ildasm, see [`PeImage::resolve_var_name`]
+    /// for their names. Custom modifiers other than the one marking `in` are left out.
+    pub fn format_type(&self, t: &SigType) -> String {
+        match t {
+            SigType::Void => "void".to_string(),
+            SigType::Boolean => "bool".to_string(),
+            SigType::Char => "char".to_string(),
+            SigType::I1 => "sbyte".to_string(),
+            SigType::U1 => "byte".to_string(),
+            SigType::I2 => "short".to_string(),
+            SigType::U2 => "ushort".to_string(),
+            SigType::I4 => "int".to_string(),
+            SigType::U4 => "uint".to_string(),
+            SigType::I8 => "long".to_string(),
+            SigType::U8 => "ulong".to_string(),
+            SigType::R4 => "float".to_string(),
+            SigType::R8 => "double".to_string(),
+            SigType::I => "nint".to_string(),
+            SigType::U => "nuint".to_string(),
+            SigType::String => "string".to_string(),
+            SigType::Object => "object".to_string(),
+            SigType::TypedByRef => "TypedReference".to_string(),
+            SigType::Class(index) | SigType::ValueType(index) => self.csharp_type_name(*index),
+            SigType::SzArray(element) => format!("{}[]", self.format_type(element)),
+            SigType::Array { element, rank, .. } => {
+                format!("{}[{}]", self.format_type(element), ",".repeat((*rank as usize).saturating_sub(1)))
+            }
+            SigType::Ptr(element) => format!("{}*", self.format_type(element)),
+            SigType::ByRef(element) => format!("ref {}", self.format_type(element)),
+            SigType::Pinned(element) => self.format_type(element),
+            SigType::Var(number) => format!("!{}", number),
+            SigType::MVar(number) => format!("!!{}", number),
+            SigType::GenericInst { base, args } => {
+                let args: Vec<String> = args.iter().map(|arg| self.format_type(arg)).collect();
+                format!("{}<{}>", self.format_type(base), args.join(", "))
+            }
+            SigType::Modified { modifiers, inner } => {
+                let is_in = modifiers.iter()
+                    .any(|modifier| modifier.required && self.type_name(modifier.type_ref).as_deref() == Some(IN_ATTRIBUTE));
+                match inner.as_ref() {
+                    SigType::ByRef(element) if is_in => format!("in {}", self.format_type(element)),
+                    inner => self.format_type(inner),
+                }
+            }
+            SigType::FnPtr(signature) => {
+                let types: Vec<String> = signature.params.iter()
+                    .chain([&signature.return_type])
+                    .map(|param| self.format_type(param))
+                    .collect();
+                format!("delegate*<{}>", types.join(", "))
+            }
+        }
+    }
+
+    /// Returns the C# name of the type `index` points at, see [`PeImage::format_type`]. Rows that don't
+    /// exist are rendered as `Table[rid]`.
+    fn csharp_type_name(&self, index: CodedIndex) -> String {
+        if let Some(keyword) = self.type_name(index)
+            .and_then(|name| KEYWORDS.iter().find(|(full_name, _)| *full_name == name))
+            .map(|(_, keyword)| keyword.to_string()) {
+            return keyword;
+        }
+
+        let name = match index.table {
+            TableKind::TypeDef => self.get_type_def(index.index).map(|mut type_def| {
+                let mut name = strip_arity(&self.get_string(type_def.type_name)).to_string();
+                // A malformed NestedClass table could contain a cycle, so stop after visiting every type.
+                for _ in 0..self.table::<TypeDef>().len() {
+                    let Some(enclosing) = self.enclosing_type(type_def) else {
+                        break;
+                    };
+                    name = format!("{}.{}", strip_arity(&self.get_string(enclosing.type_name)), name);
+                    type_def = enclosing;
+                }
+                name
+            }),
+            TableKind::TypeRef => self.get_type_ref(index.index).map(|mut type_ref| {
+                let mut name = strip_arity(&self.get_string(type_ref.type_name)).to_string();
+                for _ in 0..self.table::<TypeRef>().len() {
+                    let scope = type_ref.resolution_scope;
+                    let Some(enclosing) = self.get_type_ref(scope.index).filter(|_| scope.table == TableKind::TypeRef) else {
+                        break;
+                    };
+                    name = format!("{}.{}", strip_arity(&self.get_string(enclosing.type_name)), name);
+                    type_ref = enclosing;
+                }
+                name
+            }),
+            TableKind::TypeSpec => self.table::<TypeSpec>().get(index.index)
+                .and_then(|type_spec| self.get_type_spec(type_spec).ok())
+                .map(|sig_type| self.format_type(&sig_type)),
+            _ => None,
+        };
+
+        name.unwrap_or_else(|| format!("{}[{}]", index.table.name(), index.index))
+    }
+}
+
+/// Strips the `` `n `` suffix the compiler appends to the name of a generic type with `n` parameters.
+fn strip_arity(name: &str) -> &str {
+    name.split_once('`').map_or(name, |(name, _)| name)
+}
@@ -1,4 +1,5 @@
 
+use std::borrow::Cow;
 use std::io::BufRead;
 
 use super::*;
@@ -80,11 +81,13 @@ pub struct MetadataHeader {
 }
 
 impl MetadataHeader {
-    pub fn from(buffer: &mut Buffer) -> Result<MetadataHeader, std::io::Error> {
+    pub fn from(buffer: &mut Cursor<impl AsRef<[u8]>>) -> Result<MetadataHeader, std::io::Error> {
         let signature = buffer.read_u32::<LittleEndian>()?;
 
         // See Description of Signature field in the table above
-        assert!(signature == 0x424A5342, "Invalid metadata signature: 0x{:X}", signature);
+        if signature != 0x424A5342 {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Invalid metadata signature: 0x{:X}", signature)));
+        }
 
         let major_version = buffer.read_u16::<LittleEndian>()?;
         let minor_version = buffer.read_u16::<LittleEndian>()?;
@@ -140,7 +143,7 @@ pub struct StreamHeader {
 }
 
 impl StreamHeader {
-    pub fn from(buffer: &mut Buffer) -> Result<StreamHeader, std::io::Error> {
+    pub fn from(buffer: &mut Cursor<impl AsRef<[u8]>>) -> Result<StreamHeader, std::io::Error> {
         let offset = buffer.read_u32::<LittleEndian>()?;
         let size = buffer.read_u32::<LittleEndian>()?;
 
@@ -148,7 +151,7 @@ impl StreamHeader {
         buffer.read_until(0, &mut name)?;
         
         // Padding to the next 4-byte boundary
-        let padding = 4 - (name.len() % 4);
+        let padding = (4 - (name.len() % 4)) % 4;
         let mut padding = vec![0u8; padding];
         buffer.read_exact(&mut padding)?;
         
@@ -298,6 +301,9 @@ pub struct NtSpecificFields {
 }
 
 impl NtSpecificFields {
+    pub const IMAGE_SUBSYSTEM_WINDOWS_GUI: u16 = 0x2;
+    pub const IMAGE_SUBSYSTEM_WINDOWS_CUI: u16 = 0x3;
+
     pub fn from(slice: &[u8; 68]) -> NtSpecificFields {
         NtSpecificFields {
             image: u32::from_le_bytes(slice[0..4].try_into().unwrap()),
@@ -483,6 +489,13 @@ impl SectionHeader {
             characteristics: SectionCharacteristics::new(u32::from_le_bytes(slice[36..40].try_into().unwrap())),
         }
     }
+
+    /// Returns the name of the section without its null padding. A name of exactly eight characters has no
+    /// terminator and is returned whole.
+    pub fn name_str(&self) -> Cow<'_, str> {
+        let length = self.name.iter().position(|&byte| byte == 0).unwrap_or(self.name.len());
+        String::from_utf8_lossy(&self.name[..length])
+    }
 }
 
 /// # [II.25.3.3] CLI header 
@@ -541,3 +554,52 @@ impl CliHeader {
         }
     }
 }
+
+/// The entry point of an image, as given by the *EntryPointToken* field of the [`CliHeader`].
+///
+/// The token is either a [`MethodDef`] defined in this module, a [`File`] in a multi-module assembly whose
+/// entry point is defined in another module, or 0 for a library without an entry point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryPointKind {
+    MethodDef(u32),
+    File(u32),
+    None,
+}
+
+/// # [II.25.3.3.3] Vtable fixup 
+/// 
+/// Certain languages, which choose not to follow the common type system runtime model, can have virtual 
+/// functions which need to be represented in a v-table. These v-tables are laid out by the compiler, not by 
+/// the runtime. Finding the correct v-table slot and calling indirectly through the value held in that slot is 
+/// also done by the compiler. The VtableFixups field in the runtime header contains the location and size of 
+/// an array of Vtable Fixups (§II.15.5.1). V-tables shall be emitted into a read-write section of the PE file. 
+/// 
+/// Each entry in this array describes a contiguous array of v-table slots of the specified size. Each slot 
+/// starts out initialized to the metadata token value for the method they need to call. At image load time, 
+/// the CLI Loader will turn each entry into a pointer to machine code for the CPU and can be called directly. 
+/// 
+/// | Offset | Size | Field          | Description |
+/// | ------ | ---- | -------------- | ----------- |
+/// | 0      | 4    | VirtualAddress | RVA of Vtable |
+/// | 4      | 2    | Size           | Number of entries in Vtable |
+/// | 6      | 2    | Type           | Type of the entries, see [`VTableFixupFlags`]. |
+/// 
+/// [II.25.3.3.3]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=310
+#[derive(Debug, Clone, Copy)]
+pub struct VTableFixup {
+    pub rva: u32,
+    pub count: u16,
+    pub type_: VTableFixupFlags,
+}
+
+impl VTableFixup {
+    pub const SIZE: usize = 8;
+
+    pub fn from(slice: &[u8; 8]) -> VTableFixup {
+        VTableFixup {
+            rva: u32::from_le_bytes(slice[0..4].try_into().unwrap()),
+            count: u16::from_le_bytes(slice[4..6].try_into().unwrap()),
+            type_: VTableFixupFlags::new(u16::from_le_bytes(slice[6..8].try_into().unwrap())),
+        }
+    }
+}
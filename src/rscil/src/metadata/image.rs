@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap, sync::OnceLock};
 
-use crate::{cast_row, StringIndex};
+use crate::{cast_row, BlobIndex, GuidIndex, StringIndex};
 
 use super::*;
 
@@ -18,49 +18,210 @@ macro_rules! define_getter {
             if (index == 0) {
                 return None;
             }
-            self.streams.metadata.get_table(TableKind::$row).get((index - 1) as usize).map(|row| cast_row!(Row::$row, row))
+            self.streams.metadata.try_get_table(TableKind::$row)?.get((index - 1) as usize).map(|row| cast_row!(Row::$row, row))
+        }
+    };
+}
+
+macro_rules! define_iterators {
+    ($($name:ident: $row:ident,)*) => {
+        $(
+            #[doc = concat!("Iterates the rows of the *", stringify!($row), "* table, see [`", stringify!($row), "`]. Yields nothing if the table is absent.")]
+            pub fn $name(&self) -> impl Iterator<Item = &$row> + '_ {
+                self.table::<$row>().iter()
+            }
+        )*
+
+        /// Never called, fails to compile if a table is missing from the list.
+        #[allow(dead_code)]
+        fn iterators_cover_every_table(kind: TableKind) {
+            match kind {
+                $(TableKind::$row => (),)*
+            }
         }
     };
 }
 
 pub struct PeImage {
     pub filename : String,
+    pub pe_header: PeHeader,
+    pub optional_header: PeOptionalHeader,
     pub cli_header: CliHeader,
     pub metadata_header: MetadataHeader,
     pub streams: Streams,
     pub buffer: PeParser,
 
-    methods: HashMap<u32, MethodBody>,
+    /// Method bodies indexed by *MethodDef* row index - 1, parsed on first access.
+    methods: Vec<OnceLock<MethodBody>>,
+    /// The *NestedClass* table indexed in both directions, built on first access.
+    nested_classes: OnceLock<NestedClassIndex>,
+    /// *CustomAttribute* row indices by their *Parent*, in table order, built on first access.
+    custom_attributes: OnceLock<HashMap<CodedIndex, Vec<u32>>>,
+    /// *InterfaceImpl* row indices by their *Class*, in table order, built on first access.
+    interface_impls: OnceLock<HashMap<u32, Vec<u32>>>,
+    /// The tables flagged as sorted that aren't, see [`MetadataStream::verify_sorted`], computed on first access.
+    unsorted_tables: OnceLock<Vec<TableKind>>,
+    /// The *TypeDef* owning each *MethodDef* and *Field* row, built on first access.
+    member_owners: OnceLock<MemberOwnerIndex>,
+    pub(crate) warnings: Vec<ParseWarning>,
 }
 
-impl PeImage {
-    pub fn new(filename: String, cli_header: CliHeader, metadata_header: MetadataHeader, streams: Streams, mut buffer: PeParser) -> PeImage {
+#[derive(Default)]
+struct NestedClassIndex {
+    /// Nested *TypeDef* row index to enclosing row index.
+    enclosing: HashMap<u32, u32>,
+    /// Enclosing *TypeDef* row index to its nested row indices, in table order.
+    nested: HashMap<u32, Vec<u32>>,
+}
 
-        let methods = Self::construct_method_body_map(streams.metadata.get_table(TableKind::MethodDef), &mut buffer);
+#[derive(Default)]
+struct MemberOwnerIndex {
+    /// *MethodDef* row index to the *TypeDef* row index whose *MethodList* run contains it.
+    methods: HashMap<u32, u32>,
+    /// *Field* row index to the *TypeDef* row index whose *FieldList* run contains it.
+    fields: HashMap<u32, u32>,
+}
+
+impl PeImage {
+    pub fn new(filename: String, pe_header: PeHeader, optional_header: PeOptionalHeader, cli_header: CliHeader, metadata_header: MetadataHeader, streams: Streams, buffer: PeParser) -> PeImage {
+        let method_count = streams.metadata.context().get_row_count(TableKind::MethodDef);
 
         PeImage {
             filename,
+            pe_header,
+            optional_header,
             cli_header,
             metadata_header,
             streams,
             buffer,
-            methods,
+            methods: (0..method_count).map(|_| OnceLock::new()).collect(),
+            nested_classes: OnceLock::new(),
+            custom_attributes: OnceLock::new(),
+            interface_impls: OnceLock::new(),
+            unsorted_tables: OnceLock::new(),
+            member_owners: OnceLock::new(),
+            warnings: Vec::new(),
         }
     }
 
-    fn construct_method_body_map(methods: &Table, buffer: &mut PeParser) -> HashMap<u32, MethodBody> {
-        let mut map = HashMap::new();
-        for (_, row) in methods.iter().enumerate() {
-            if let Some(method) = MethodDef::from_row(row) {
-                let body = buffer.read_method_body(method.rva).unwrap();
-                map.insert(method.index, body);
-            }
+    /// Parses every method body that hasn't been parsed yet, see [`PeParser::read`]. Methods without a
+    /// body are skipped.
+    pub fn load_method_bodies(&self) -> Result<(), std::io::Error> {
+        for method in self.table::<MethodDef>() {
+            self.try_get_method_body(method)?;
         }
-        map
+        Ok(())
     }
 
-    pub fn get_string(&self, index: StringIndex) -> &String {
-        self.streams.strings.get(index.0).unwrap()
+    /// Returns the body of `method`, parsing it on first access, or `None` if it has no body, see
+    /// [`PeImage::get_method_body`]. A method whose *RVA* is 0 isn't read at all, rather than reading a
+    /// header at a bogus address.
+    ///
+    /// Fails if the body can't be parsed. [`PeParser::read`] parses every body up front, so this only
+    /// happens for images read without method bodies, see [`ParseOptions::parse_method_bodies`].
+    pub fn try_get_method_body(&self, method: &MethodDef) -> Result<Option<&MethodBody>, LushError> {
+        if method.rva == 0 {
+            return Ok(None);
+        }
+
+        let cell = &self.methods[method.index as usize - 1];
+        if let Some(body) = cell.get() {
+            return Ok(Some(body));
+        }
+
+        let body = self.buffer.read_method_body(method.rva)?;
+        Ok(Some(cell.get_or_init(|| body)))
+    }
+
+    /// Returns the recoverable oddities found while parsing the image, see [`ParseWarning`].
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Returns the string at `index` in the #Strings heap, without its null terminator. An index outside the
+    /// heap yields an empty string.
+    pub fn get_string(&self, index: StringIndex) -> Cow<'_, str> {
+        self.streams.strings.get(index.0).unwrap_or_default()
+    }
+
+    /// Returns the #US heap string referenced by a `ldstr` token, `None` for any other kind of token.
+    pub fn get_user_string(&self, token: MetadataToken) -> Option<String> {
+        match token {
+            MetadataToken::UserString(index) => self.streams.user_strings.get(index),
+            MetadataToken::Table(..) => None,
+        }
+    }
+
+    pub fn get_blob(&self, index: BlobIndex) -> Option<&[u8]> {
+        self.streams.blobs.get(index)
+    }
+
+    /// Decodes the *Signature* blob of a [`MethodDef`] row, see [`MethodSignature::parse`].
+    pub fn get_method_signature(&self, row: &MethodDef) -> Result<MethodSignature, std::io::Error> {
+        let blob = self.get_blob(row.signature)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "Missing method signature blob"))?;
+        MethodSignature::parse(blob, self.streams.metadata.context())
+    }
+
+    /// Decodes the *Signature* blob of a [`Field`] row, see [`FieldSignature::parse`].
+    pub fn get_field_signature(&self, row: &Field) -> Result<FieldSignature, std::io::Error> {
+        let blob = self.get_blob(row.signature)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "Missing field signature blob"))?;
+        FieldSignature::parse(blob, self.streams.metadata.context())
+    }
+
+    /// Decodes the *Type* blob of a [`Property`] row, see [`PropertySignature::parse`].
+    pub fn get_property_signature(&self, row: &Property) -> Result<PropertySignature, LushError> {
+        let blob = self.get_blob(row.type_).ok_or(LushError::InvalidSignature(row.type_.0))?;
+        PropertySignature::parse(blob, self.streams.metadata.context())
+            .map_err(|_| LushError::InvalidSignature(row.type_.0))
+    }
+
+    /// # II.23.2.14 TypeSpec
+    /// [...]
+    ///
+    /// Decodes the *Signature* blob of a [`TypeSpec`] row, which holds a single *Type*, e.g. the generic
+    /// instantiation `List<string>`.
+    pub fn get_type_spec(&self, row: &TypeSpec) -> Result<SigType, LushError> {
+        let mut blob = self.get_blob(row.signature).ok_or(LushError::InvalidSignature(row.signature.0))?;
+        SigType::parse(&mut blob, self.streams.metadata.context())
+            .map_err(|_| LushError::InvalidSignature(row.signature.0))
+    }
+
+    /// Decodes the *Instantiation* blob of a [`MethodSpec`] row, see [`MethodSpecSignature::parse`].
+    pub fn get_method_spec(&self, row: &MethodSpec) -> Result<MethodSpecSignature, LushError> {
+        let blob = self.get_blob(row.instantiation).ok_or(LushError::InvalidSignature(row.instantiation.0))?;
+        MethodSpecSignature::parse(blob, self.streams.metadata.context())
+            .map_err(|_| LushError::InvalidSignature(row.instantiation.0))
+    }
+
+    /// Decodes the local variable types of a method body from the *StandAloneSig* referenced by
+    /// [`MethodBody::local_var_sig_token`], see [`LocalVarSig::parse`].
+    pub fn get_local_variables(&self, body: &MethodBody) -> Result<Vec<SigType>, std::io::Error> {
+        let invalid = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string());
+
+        let rid = match body.local_var_sig_token {
+            None => return Ok(Vec::new()),
+            Some(MetadataToken::Table(TableKind::StandAloneSig, rid)) => rid,
+            Some(token) => return Err(invalid(&format!("{:?} is not a StandAloneSig token", token))),
+        };
+        let row = self.get_stand_alone_sig(rid)
+            .ok_or_else(|| invalid("Local variable signature token is out of range"))?;
+        let blob = self.get_blob(row.signature)
+            .ok_or_else(|| invalid("Missing local variable signature blob"))?;
+
+        LocalVarSig::parse(blob, self.streams.metadata.context()).map(|signature| signature.locals)
+    }
+
+    /// Decodes the *Signature* blob of a [`StandAloneSig`] row, see [`StandaloneSignature::parse`].
+    pub fn get_standalone_sig(&self, row: &StandAloneSig) -> Result<StandaloneSignature, LushError> {
+        let blob = self.get_blob(row.signature).ok_or(LushError::InvalidSignature(row.signature.0))?;
+        StandaloneSignature::parse(blob, self.streams.metadata.context())
+            .map_err(|_| LushError::InvalidSignature(row.signature.0))
+    }
+
+    pub fn get_guid(&self, index: GuidIndex) -> Option<[u8; 16]> {
+        self.streams.guids.get(index)
     }
 
     /// # [II.22.2] Assembly : 0x20
@@ -70,7 +231,7 @@ impl PeImage {
     /// 
     /// [II.22.2]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=237
     pub fn get_assembly(&self) -> Option<&Assembly> {
-        self.streams.metadata.get_table(TableKind::Assembly).first().map(|row| cast_row!(Row::Assembly, row))
+        self.streams.metadata.try_get_table(TableKind::Assembly)?.first().map(|row| cast_row!(Row::Assembly, row))
     }
 
     /// # [II.22.30] Module : 0x00
@@ -83,6 +244,17 @@ impl PeImage {
         cast_row!(Some(Module), self.streams.metadata.get_table(TableKind::Module).first()).unwrap()
     }
 
+    /// Returns the module version id of the [`Module`] row, see [`PeImage::get_guid`].
+    pub fn mvid(&self) -> Option<[u8; 16]> {
+        self.get_guid(self.get_module().mvid)
+    }
+
+    /// Returns the row a [`CodedIndex`] points at, `None` for the null index 0 or a row that doesn't exist.
+    pub fn resolve(&self, index: CodedIndex) -> Option<&Row> {
+        let rid = index.index.checked_sub(1)?;
+        self.streams.metadata.try_get_table(index.table)?.get(rid as usize)
+    }
+
     define_getter!(get_method_def, MethodDef);
     define_getter!(get_type_def, TypeDef);
     define_getter!(get_type_ref, TypeRef);
@@ -91,8 +263,896 @@ impl PeImage {
     define_getter!(get_interface_impl, InterfaceImpl);
     define_getter!(get_member_ref, MemberRef);
     define_getter!(get_assembly_ref, AssemblyRef);
+    define_getter!(get_stand_alone_sig, StandAloneSig);
+
+    define_iterators! {
+        assemblies: Assembly,
+        assembly_oses: AssemblyOS,
+        assembly_processors: AssemblyProcessor,
+        assembly_refs: AssemblyRef,
+        assembly_ref_oses: AssemblyRefOS,
+        assembly_ref_processors: AssemblyRefProcessor,
+        class_layouts: ClassLayout,
+        constants: Constant,
+        all_custom_attributes: CustomAttribute,
+        decl_securities: DeclSecurity,
+        enc_logs: ENCLog,
+        enc_maps: ENCMap,
+        event_maps: EventMap,
+        events: Event,
+        event_ptrs: EventPtr,
+        exported_types: ExportedType,
+        fields: Field,
+        field_layouts: FieldLayout,
+        field_marshals: FieldMarshal,
+        field_ptrs: FieldPtr,
+        field_rvas: FieldRVA,
+        files: File,
+        generic_params: GenericParam,
+        generic_param_constraints: GenericParamConstraint,
+        impl_maps: ImplMap,
+        interface_impls: InterfaceImpl,
+        manifest_resources: ManifestResource,
+        member_refs: MemberRef,
+        method_defs: MethodDef,
+        method_impls: MethodImpl,
+        method_ptrs: MethodPtr,
+        method_semantics: MethodSemantics,
+        method_specs: MethodSpec,
+        modules: Module,
+        module_refs: ModuleRef,
+        nested_classes: NestedClass,
+        params: Param,
+        param_ptrs: ParamPtr,
+        properties: Property,
+        property_maps: PropertyMap,
+        property_ptrs: PropertyPtr,
+        stand_alone_sigs: StandAloneSig,
+        type_defs: TypeDef,
+        type_refs: TypeRef,
+        type_specs: TypeSpec,
+    }
+
+    /// Resolves the declaring type and name of a [`MemberRef`], and whether it refers to a field or a method.
+    pub fn member_ref_info(&self, row: &MemberRef) -> MemberRefInfo {
+        let class = row.class;
+        let declaring_type = match class.table {
+            TableKind::TypeDef => self.get_type_def(class.index)
+                .map(|type_def| self.full_type_name(type_def.type_namespace, type_def.type_name)),
+            TableKind::TypeRef => self.get_type_ref(class.index)
+                .map(|type_ref| self.full_type_name(type_ref.type_namespace, type_ref.type_name)),
+            TableKind::ModuleRef => self.table::<ModuleRef>().get(class.index)
+                .map(|module_ref| self.get_string(module_ref.name).to_string()),
+            TableKind::MethodDef => self.get_method_def(class.index)
+                .map(|method_def| self.get_string(method_def.name).to_string()),
+            TableKind::TypeSpec => self.type_full_name(class),
+            _ => None,
+        };
+
+        let kind = match self.get_blob(row.signature).and_then(|signature| signature.first()) {
+            Some(&MemberRefInfo::FIELD) => MemberKind::Field,
+            _ => MemberKind::Method,
+        };
+
+        MemberRefInfo {
+            declaring_type,
+            name: self.get_string(row.name).to_string(),
+            kind,
+        }
+    }
+
+    pub(crate) fn full_type_name(&self, namespace: StringIndex, name: StringIndex) -> String {
+        let namespace = self.get_string(namespace);
+        let name = self.get_string(name);
+        if namespace.is_empty() {
+            name.to_string()
+        }
+        else {
+            format!("{}.{}", namespace, name)
+        }
+    }
+
+    /// Returns the fully-qualified name of the type `index` points at:
+    ///
+    /// * A *TypeDef* as `Namespace.Name`, prefixed with its enclosing types for nested types, e.g.
+    ///   `Namespace.Outer+Inner`.
+    /// * A *TypeRef* like a *TypeDef*, prefixed with the assembly it's resolved in, e.g.
+    ///   `[mscorlib]System.Console`.
+    /// * A *TypeSpec* by decoding its signature, e.g. `[mscorlib]System.Collections.Generic.List<!0>`.
+    ///
+    /// Returns `None` for other tables or rows that don't exist.
+    pub fn type_full_name(&self, index: CodedIndex) -> Option<String> {
+        self.type_full_name_with(index, None)
+    }
+
+    /// Same as [`PeImage::type_full_name`], with the generic parameters of a *TypeSpec* rendered by name
+    /// as declared by `owner`, the *TypeDef* or *MethodDef* the type is used in, see
+    /// [`PeImage::resolve_var_name`]. E.g. `[mscorlib]System.Collections.Generic.List<T>`.
+    pub fn type_full_name_in(&self, index: CodedIndex, owner: CodedIndex) -> Option<String> {
+        self.type_full_name_with(index, Some(owner))
+    }
+
+    fn type_full_name_with(&self, index: CodedIndex, owner: Option<CodedIndex>) -> Option<String> {
+        match index.table {
+            TableKind::TypeDef => {
+                let mut type_def = self.get_type_def(index.index)?;
+                let mut name = self.full_type_name(type_def.type_namespace, type_def.type_name);
+
+                // A malformed NestedClass table could contain a cycle, so stop after visiting every type.
+                for _ in 0..self.table::<TypeDef>().len() {
+                    let Some(enclosing) = self.enclosing_type(type_def) else {
+                        break;
+                    };
+                    name = format!("{}+{}", self.full_type_name(enclosing.type_namespace, enclosing.type_name), name);
+                    type_def = enclosing;
+                }
+                Some(name)
+            }
+            TableKind::TypeRef => {
+                let mut type_ref = self.get_type_ref(index.index)?;
+                let mut name = self.full_type_name(type_ref.type_namespace, type_ref.type_name);
+
+                // A nested type is resolved in the scope of its enclosing TypeRef.
+                for _ in 0..self.table::<TypeRef>().len() {
+                    let scope = type_ref.resolution_scope;
+                    if scope.table != TableKind::TypeRef {
+                        break;
+                    }
+                    let Some(enclosing) = self.get_type_ref(scope.index) else {
+                        break;
+                    };
+                    name = format!("{}+{}", self.full_type_name(enclosing.type_namespace, enclosing.type_name), name);
+                    type_ref = enclosing;
+                }
+
+                match type_ref.resolution_scope.table {
+                    TableKind::AssemblyRef => match self.get_assembly_ref(type_ref.resolution_scope.index) {
+                        Some(assembly_ref) => Some(format!("[{}]{}", self.get_string(assembly_ref.name), name)),
+                        None => Some(name),
+                    },
+                    _ => Some(name),
+                }
+            }
+            TableKind::TypeSpec => {
+                let type_spec = self.table::<TypeSpec>().get(index.index)?;
+                let sig_type = self.get_type_spec(type_spec).ok()?;
+                Some(self.sig_type_name(&sig_type, owner))
+            }
+            _ => None,
+        }
+    }
+
+    /// Renders a signature type with the names used by [`PeImage::type_full_name`]. Generic parameters are
+    /// named as declared by `owner`, or rendered as `!n` and `!!n` like ildasm when they can't be resolved.
+    pub(crate) fn sig_type_name(&self, sig_type: &SigType, owner: Option<CodedIndex>) -> String {
+        let named = |index: &CodedIndex| self.type_full_name_with(*index, owner)
+            .unwrap_or_else(|| format!("{}[{}]", index.table.name(), index.index));
+
+        match sig_type {
+            SigType::Void => "System.Void".to_string(),
+            SigType::Boolean => "System.Boolean".to_string(),
+            SigType::Char => "System.Char".to_string(),
+            SigType::I1 => "System.SByte".to_string(),
+            SigType::U1 => "System.Byte".to_string(),
+            SigType::I2 => "System.Int16".to_string(),
+            SigType::U2 => "System.UInt16".to_string(),
+            SigType::I4 => "System.Int32".to_string(),
+            SigType::U4 => "System.UInt32".to_string(),
+            SigType::I8 => "System.Int64".to_string(),
+            SigType::U8 => "System.UInt64".to_string(),
+            SigType::R4 => "System.Single".to_string(),
+            SigType::R8 => "System.Double".to_string(),
+            SigType::I => "System.IntPtr".to_string(),
+            SigType::U => "System.UIntPtr".to_string(),
+            SigType::String => "System.String".to_string(),
+            SigType::Object => "System.Object".to_string(),
+            SigType::TypedByRef => "System.TypedReference".to_string(),
+            SigType::Class(index) | SigType::ValueType(index) => named(index),
+            SigType::SzArray(element) => format!("{}[]", self.sig_type_name(element, owner)),
+            SigType::Ptr(element) => format!("{}*", self.sig_type_name(element, owner)),
+            SigType::ByRef(element) => format!("{}&", self.sig_type_name(element, owner)),
+            SigType::Pinned(element) => self.sig_type_name(element, owner),
+            SigType::Var(number) => owner.and_then(|owner| self.resolve_var_name(owner, false, *number))
+                .unwrap_or_else(|| format!("!{}", number)),
+            SigType::MVar(number) => owner.and_then(|owner| self.resolve_var_name(owner, true, *number))
+                .unwrap_or_else(|| format!("!!{}", number)),
+            SigType::Array { element, rank, .. } => {
+                format!("{}[{}]", self.sig_type_name(element, owner), ",".repeat((*rank as usize).saturating_sub(1)))
+            }
+            SigType::GenericInst { base, args } => {
+                let args: Vec<String> = args.iter().map(|arg| self.sig_type_name(arg, owner)).collect();
+                format!("{}<{}>", self.sig_type_name(base, owner), args.join(","))
+            }
+            SigType::Modified { modifiers, inner } => {
+                let mut name = self.sig_type_name(inner, owner);
+                for modifier in modifiers {
+                    let kind = if modifier.required { "modreq" } else { "modopt" };
+                    name.push_str(&format!(" {}({})", kind, named(&modifier.type_ref)));
+                }
+                name
+            }
+            SigType::FnPtr(signature) => {
+                let params: Vec<String> = signature.params.iter().map(|param| self.sig_type_name(param, owner)).collect();
+                format!("method {} *({})", self.sig_type_name(&signature.return_type, owner), params.join(","))
+            }
+        }
+    }
+
+    /// Returns the full name of the *TypeDef* or *TypeRef* `index` points at, `None` for any other table.
+    pub(crate) fn type_name(&self, index: CodedIndex) -> Option<String> {
+        match index.table {
+            TableKind::TypeDef => self.get_type_def(index.index)
+                .map(|type_def| self.full_type_name(type_def.type_namespace, type_def.type_name)),
+            TableKind::TypeRef => self.get_type_ref(index.index)
+                .map(|type_ref| self.full_type_name(type_ref.type_namespace, type_ref.type_name)),
+            _ => None,
+        }
+    }
+
+    /// Returns the rows owned by `owner` through a list column such as *TypeDef.MethodList*: from the
+    /// row that column points at, up to the row the next owner's column points at, or the end of the
+    /// target table for the last owner.
+    ///
+    /// When the target table has a pointer table, see [`TableKind::pointer_table`], the list column
+    /// indexes that instead and its rows are resolved to the rows of the target table they point at.
+    pub(crate) fn list_run<O: TableRow>(&self, owner: &O, list: impl Fn(&O) -> CodedIndex) -> Vec<u32> {
+        let target = list(owner).table;
+        let metadata = &self.streams.metadata;
+        let pointers = target.pointer_table().and_then(|kind| metadata.try_get_table(kind));
+        let target_len = pointers.map_or_else(|| metadata.context().get_row_count(target), |rows| rows.len() as u32);
+
+        let start = list(owner).index;
+        let end = self.table::<O>()
+            .get(owner.index() + 1)
+            .map_or(target_len + 1, |next| list(next).index);
+        let run = start..end.max(start);
+
+        match pointers {
+            Some(rows) => run
+                .filter_map(|rid| rows.get((rid as usize).checked_sub(1)?))
+                .filter_map(pointer_target)
+                .collect(),
+            None => run.collect(),
+        }
+    }
+
+    /// Collects the rows of `T` owned by `owner` through the list column `list`, see [`PeImage::list_run`].
+    fn list_rows<O: TableRow, T: TableRow>(&self, owner: &O, list: impl Fn(&O) -> CodedIndex) -> Vec<&T> {
+        let rows = self.table::<T>();
+        self.list_run(owner, list)
+            .into_iter()
+            .filter_map(|rid| rows.get(rid))
+            .collect()
+    }
+
+    /// # [II.22.37] TypeDef : 0x02
+    /// [...]
+    /// 
+    /// * *FieldList* (an index into the Field table; it marks the first of a contiguous run of Fields owned 
+    ///   by this Type). The run continues to the smaller of: 
+    ///   * the last row of the Field table 
+    ///   * the next run of Fields, found by inspecting the FieldList of the next row in this TypeDef table 
+    /// 
+    /// [II.22.37]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=269
+    pub fn fields_of(&self, type_def: &TypeDef) -> Vec<&Field> {
+        self.list_rows(type_def, |row| row.field_list)
+    }
+
+    /// Same as [`PeImage::fields_of`], for the run of methods starting at *TypeDef.MethodList*.
+    pub fn methods_of(&self, type_def: &TypeDef) -> Vec<&MethodDef> {
+        self.list_rows(type_def, |row| row.method_list)
+    }
+
+    /// Returns the top-level type named `name` in `namespace`, comparing against the resolved *#Strings*
+    /// entries. Types in the global namespace, such as the `<Module>` pseudo-type of the first *TypeDef*
+    /// row, are found with an empty `namespace`.
+    ///
+    /// Nested types are skipped, as their *TypeNamespace* is usually empty and their names are only
+    /// unique within the enclosing type, see [`PeImage::nested_types`].
+    pub fn find_type_by_name(&self, namespace: &str, name: &str) -> Option<&TypeDef> {
+        self.type_defs().find(|type_def| {
+            self.get_string(type_def.type_name) == name
+                && self.get_string(type_def.type_namespace) == namespace
+                && self.enclosing_type(type_def).is_none()
+        })
+    }
+
+    /// Returns the first method of `type_def` named `name`, see [`PeImage::methods_of`]. Overloads share
+    /// a name, so this is the one declared first.
+    pub fn find_method_in_type(&self, type_def: &TypeDef, name: &str) -> Option<&MethodDef> {
+        self.methods_of(type_def)
+            .into_iter()
+            .find(|method| self.get_string(method.name) == name)
+    }
+
+    /// # [II.22.26] MethodDef : 0x06
+    /// [...]
+    /// 
+    /// * *ParamList* (an index into the Param table). It marks the first of a contiguous run of Parameters 
+    ///   owned by this method. The run continues to the smaller of: 
+    ///   * the last row of the Param table 
+    ///   * the next run of Parameters, found by inspecting the ParamList of the next row in the MethodDef table 
+    /// 
+    /// A method without parameters has the same *ParamList* as the method after it.
+    /// 
+    /// [II.22.26]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=259
+    pub fn params_of(&self, method: &MethodDef) -> Vec<&Param> {
+        self.list_rows(method, |row| row.param_list)
+    }
+
+    /// Collects the members of `type_def` that are visible outside its assembly, that is `public`,
+    /// `protected` or `protected internal`. Properties and events are visible if any of their accessors is.
+    pub fn public_surface(&self, type_def: &TypeDef) -> TypeSurface<'_> {
+        let public_methods = self.methods_of(type_def).into_iter()
+            .filter(|method| method.flags.access().is_visible_outside_assembly())
+            .collect();
+        let public_fields = self.fields_of(type_def).into_iter()
+            .filter(|field| field.flags.access().is_visible_outside_assembly())
+            .collect();
+
+        let properties = self.table::<Property>();
+        let public_properties = self.table::<PropertyMap>().iter()
+            .find(|map| map.parent.index == type_def.index)
+            .map(|map| self.list_run(map, |row| row.property_list))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|rid| properties.get(rid))
+            .filter(|property| self.has_visible_accessor(TableKind::Property, property.index))
+            .collect();
+
+        let events = self.table::<Event>();
+        let public_events = self.table::<EventMap>().iter()
+            .find(|map| map.parent.index == type_def.index)
+            .map(|map| self.list_run(map, |row| row.event_list))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|rid| events.get(rid))
+            .filter(|event| self.has_visible_accessor(TableKind::Event, event.index))
+            .collect();
+
+        TypeSurface {
+            methods: public_methods,
+            fields: public_fields,
+            properties: public_properties,
+            events: public_events,
+        }
+    }
+
+    fn has_visible_accessor(&self, kind: TableKind, index: u32) -> bool {
+        self.semantics_of(kind, index)
+            .any(|(_, method)| method.flags.access().is_visible_outside_assembly())
+    }
+
+    /// # [II.22.28] MethodSemantics : 0x18
+    /// [...]
+    ///
+    /// Returns the getter, setter and other methods of `property`.
+    ///
+    /// [II.22.28]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=263
+    pub fn property_accessors(&self, property: &Property) -> PropertyAccessors<'_> {
+        let mut accessors = PropertyAccessors::default();
+        for (semantics, method) in self.semantics_of(TableKind::Property, property.index) {
+            if semantics.contains(MethodSemanticsAttributes::GETTER) {
+                accessors.getter = Some(method);
+            }
+            else if semantics.contains(MethodSemanticsAttributes::SETTER) {
+                accessors.setter = Some(method);
+            }
+            else {
+                accessors.others.push(method);
+            }
+        }
+        accessors
+    }
+
+    /// Returns the add, remove, fire and other methods of `event`, see [`PeImage::property_accessors`].
+    pub fn event_accessors(&self, event: &Event) -> EventAccessors<'_> {
+        let mut accessors = EventAccessors::default();
+        for (semantics, method) in self.semantics_of(TableKind::Event, event.index) {
+            if semantics.contains(MethodSemanticsAttributes::ADD_ON) {
+                accessors.add_on = Some(method);
+            }
+            else if semantics.contains(MethodSemanticsAttributes::REMOVE_ON) {
+                accessors.remove_on = Some(method);
+            }
+            else if semantics.contains(MethodSemanticsAttributes::FIRE) {
+                accessors.fire = Some(method);
+            }
+            else {
+                accessors.others.push(method);
+            }
+        }
+        accessors
+    }
+
+    /// Returns the methods associated with row `index` of the *Event* or *Property* table and their semantics.
+    fn semantics_of(&self, kind: TableKind, index: u32) -> impl Iterator<Item = (MethodSemanticsAttributes, &MethodDef)> {
+        let association = CodedIndex::from(kind, index).encode(CodedIndexTag::HasSemantics);
+        association.map(|key| self.rows_by_key::<MethodSemantics>(key))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|semantics| Some((semantics.semantics, self.get_method_def(semantics.method.index)?)))
+    }
+
+    /// Whether the rows of the `kind` table are ordered by their primary key: the table is flagged in the
+    /// *Sorted* bit vector, and verified to be sorted on first use, see [`MetadataStream::verify_sorted`].
+    pub fn is_ordered(&self, kind: TableKind) -> bool {
+        let metadata = &self.streams.metadata;
+        metadata.is_sorted(kind)
+            && !self.unsorted_tables.get_or_init(|| metadata.verify_sorted()).contains(&kind)
+    }
 
+    /// Returns the rows of `T` whose primary key is `key`, see [`Row::sort_key`], in table order. They're
+    /// found by binary search if the table [`PeImage::is_ordered`], and by a linear scan otherwise.
+    pub(crate) fn rows_by_key<T: TableRow>(&self, key: u32) -> Vec<&T> {
+        let Some(table) = self.streams.metadata.try_get_table(T::KIND) else {
+            return Vec::new();
+        };
+
+        if self.is_ordered(T::KIND) {
+            find_range_by_key(table, Row::sort_key, key).iter().filter_map(T::from_row).collect()
+        }
+        else {
+            table.iter().filter(|row| row.sort_key() == Some(key)).filter_map(T::from_row).collect()
+        }
+    }
+
+    /// Renders every present table, its row count and each row's columns, in table number order.
+    /// Strings are inlined and coded indices are shown as `Table[rid]`.
+    pub fn dump_tables(&self) -> String {
+        let mut output = String::new();
+        for kind in self.streams.metadata.present_tables() {
+            let table = self.streams.metadata.get_table(kind);
+            output.push_str(&format!("{} (0x{:02X}): {} rows\n", kind.name(), u8::from(kind), table.len()));
+
+            for row in table {
+                let columns = row.columns(self)
+                    .into_iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                output.push_str(&format!("  [{}] {}\n", row.index(), columns));
+            }
+        }
+        output
+    }
+
+    /// Returns a typed view over the table holding rows of type `T`.
+    /// The view is empty if the table isn't present in the image.
+    pub fn table<T: TableRow>(&self) -> TableView<'_, T> {
+        match self.streams.metadata.try_get_table(T::KIND) {
+            Some(table) => TableView::new(table),
+            None => TableView::empty(),
+        }
+    }
+
+    /// Returns the body of the method with the given *MethodDef* row index, parsing it on first access.
+    /// Returns `None` if there's no such method, it has no body or its body can't be read.
+    ///
+    /// For an image read without method bodies, e.g. by [`PeParser::read_metadata_only`], body parse
+    /// errors are deferred to the first access and this returns `None` for them. Use
+    /// [`PeImage::try_get_method_body`] or [`PeImage::get_method_body_by_token`] to get the error.
+    ///
+    /// A method has no CIL body, and an *RVA* of 0, when it's [`MethodAttributes::ABSTRACT`] (which
+    /// includes every interface method), [`MethodAttributes::PINVOKE_IMPL`], or implemented by the runtime
+    /// or natively, see [`MethodImplAttributes::RUNTIME`] and [`MethodImplAttributes::NATIVE`].
     pub fn get_method_body(&self, method_index: u32) -> Option<&MethodBody> {
-        self.methods.get(&method_index)
+        let method = self.get_method_def(method_index)?;
+        self.try_get_method_body(method).ok().flatten()
+    }
+
+    /// Returns the body of the method referenced by a *MethodDef* `token`, parsing it on first access.
+    /// Fails with [`LushError::InvalidToken`] if `token` doesn't reference an existing *MethodDef* row, and
+    /// with [`LushError::NoMethodBody`] if the method has no body, see [`PeImage::get_method_body`].
+    pub fn get_method_body_by_token(&self, token: MetadataToken) -> Result<&MethodBody, LushError> {
+        let method = match token {
+            MetadataToken::Table(TableKind::MethodDef, rid) => self.get_method_def(rid),
+            _ => None,
+        };
+        let method = method.ok_or_else(|| LushError::InvalidToken(token.to_string()))?;
+        self.try_get_method_body(method)?.ok_or(LushError::NoMethodBody(token))
+    }
+
+    /// Returns the body of the entry point method named by the CLI header, see
+    /// [`PeImage::get_method_body_by_token`]. Fails with [`LushError::InvalidToken`] if the image has no
+    /// entry point or its entry point is in another module.
+    pub fn entry_point_body(&self) -> Result<&MethodBody, LushError> {
+        self.get_method_body_by_token(self.cli_header.entry_point_token)
+    }
+
+    /// # [II.22.32] NestedClass : 0x29
+    /// [...]
+    ///
+    /// Returns the type enclosing `nested`, or `None` if it isn't nested. The *NestedClass* table is
+    /// indexed once on first use.
+    ///
+    /// [II.22.32]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=266
+    pub fn enclosing_type(&self, nested: &TypeDef) -> Option<&TypeDef> {
+        let enclosing = *self.nested_class_index().enclosing.get(&nested.index)?;
+        self.get_type_def(enclosing)
+    }
+
+    /// Returns the types directly nested in `enclosing`, in table order, see [`PeImage::enclosing_type`].
+    pub fn nested_types(&self, enclosing: &TypeDef) -> Vec<&TypeDef> {
+        self.nested_class_index().nested.get(&enclosing.index)
+            .map(|nested| nested.iter().filter_map(|rid| self.get_type_def(*rid)).collect())
+            .unwrap_or_default()
+    }
+
+    /// # [II.22.8] ClassLayout : 0x0F
+    /// [...]
+    ///
+    /// Returns the packing size and class size of `type_def`, as declared by `.pack` and `.size` in ilasm
+    /// or `[StructLayout(Pack = ..., Size = ...)]` in C#. Only types with [`TypeLayout::Sequential`] or
+    /// [`TypeLayout::Explicit`] layout shall have one.
+    ///
+    /// [II.22.8]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=240
+    pub fn class_layout(&self, type_def: &TypeDef) -> Option<&ClassLayout> {
+        self.rows_by_key::<ClassLayout>(type_def.index).first().copied()
+    }
+
+    /// # [II.22.16] FieldLayout : 0x10
+    /// [...]
+    ///
+    /// Returns the byte offset of `field` within an instance of its type, as declared by `[FieldOffset]`
+    /// in C#. Only fields of types with [`TypeLayout::Explicit`] layout shall have one.
+    ///
+    /// [II.22.16]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=251
+    pub fn field_offset(&self, field: &Field) -> Option<u32> {
+        self.rows_by_key::<FieldLayout>(field.index).first().map(|row| row.offset)
     }
+
+    /// Returns the custom attributes attached to `parent`, in table order. Decode their values with
+    /// [`PeImage::decode_custom_attribute`]. They're found by binary search if the *CustomAttribute* table
+    /// [`PeImage::is_ordered`], otherwise it's indexed once on first use.
+    pub fn custom_attributes(&self, parent: CodedIndex) -> Vec<&CustomAttribute> {
+        if self.is_ordered(TableKind::CustomAttribute) {
+            return parent.encode(CodedIndexTag::HasCustomAttribute)
+                .map(|key| self.rows_by_key(key))
+                .unwrap_or_default();
+        }
+
+        let index = self.custom_attributes.get_or_init(|| {
+            let mut index = HashMap::<CodedIndex, Vec<u32>>::new();
+            for row in self.table::<CustomAttribute>() {
+                index.entry(row.parent).or_default().push(row.index);
+            }
+            index
+        });
+
+        let rows = self.table::<CustomAttribute>();
+        index.get(&parent)
+            .map(|rids| rids.iter().filter_map(|rid| rows.get(*rid)).collect())
+            .unwrap_or_default()
+    }
+
+    /// # [II.22.23] InterfaceImpl : 0x09
+    /// [...]
+    ///
+    /// Returns the interfaces `type_def` declares it implements, in table order, as *TypeDefOrRef* coded
+    /// indices, see [`PeImage::type_full_name`]. Interfaces inherited from its base types aren't included.
+    /// They're found by binary search if the *InterfaceImpl* table [`PeImage::is_ordered`], otherwise it's
+    /// indexed once on first use.
+    ///
+    /// [II.22.23]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=257
+    pub fn interfaces_of(&self, type_def: &TypeDef) -> Vec<CodedIndex> {
+        if self.is_ordered(TableKind::InterfaceImpl) {
+            return self.rows_by_key::<InterfaceImpl>(type_def.index).into_iter()
+                .map(|row| row.interface)
+                .collect();
+        }
+
+        let index = self.interface_impls.get_or_init(|| {
+            let mut index = HashMap::<u32, Vec<u32>>::new();
+            for row in self.table::<InterfaceImpl>() {
+                index.entry(row.class.index).or_default().push(row.index);
+            }
+            index
+        });
+
+        let rows = self.table::<InterfaceImpl>();
+        index.get(&type_def.index)
+            .map(|rids| rids.iter().filter_map(|rid| rows.get(*rid)).map(|row| row.interface).collect())
+            .unwrap_or_default()
+    }
+
+    /// # [II.22.20] GenericParam : 0x2A
+    /// [...]
+    ///
+    /// Returns the generic parameters of the type or method `owner`, ordered by their *Number*, that is
+    /// the index a `Var` or `MVar` signature type refers to. Empty if `owner` isn't generic, or isn't a
+    /// *TypeDef* or *MethodDef*.
+    ///
+    /// [II.22.20]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=254
+    pub fn generic_params_of(&self, owner: CodedIndex) -> Vec<&GenericParam> {
+        let mut params = owner.encode(CodedIndexTag::TypeOrMethodDef)
+            .map(|key| self.rows_by_key::<GenericParam>(key))
+            .unwrap_or_default();
+        params.sort_by_key(|param| param.number);
+        params
+    }
+
+    /// Returns the name of generic parameter `index` in the scope of the *TypeDef* or *MethodDef* `owner`:
+    /// a parameter of the method for an `MVar` (`is_method`), or of the type for a `Var`, which for a
+    /// method is its declaring type. `None` if there's no such parameter.
+    pub fn resolve_var_name(&self, owner: CodedIndex, is_method: bool, index: u32) -> Option<String> {
+        let owner = match (owner.table, is_method) {
+            (TableKind::MethodDef, true) | (TableKind::TypeDef, false) => owner,
+            (TableKind::MethodDef, false) => {
+                let type_def = self.method_owner(owner.index)?;
+                CodedIndex::from(TableKind::TypeDef, type_def.index)
+            }
+            _ => return None,
+        };
+
+        self.generic_params_of(owner).into_iter()
+            .find(|param| u32::from(param.number) == index)
+            .map(|param| self.get_string(param.name).into_owned())
+    }
+
+    /// # [II.22.21] GenericParamConstraint : 0x2C
+    /// [...]
+    ///
+    /// Returns the types `param` is constrained to derive from or implement, e.g. `IComparable<T>` for
+    /// `where T : IComparable<T>`, as *TypeDefOrRef* coded indices, see [`PeImage::type_full_name`].
+    ///
+    /// [II.22.21]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=255
+    pub fn generic_constraints_of(&self, param: &GenericParam) -> Vec<CodedIndex> {
+        self.rows_by_key::<GenericParamConstraint>(param.index).into_iter()
+            .map(|row| row.constraint)
+            .collect()
+    }
+
+    fn nested_class_index(&self) -> &NestedClassIndex {
+        self.nested_classes.get_or_init(|| {
+            let mut index = NestedClassIndex::default();
+            for row in self.table::<NestedClass>() {
+                index.enclosing.insert(row.nested_class.index, row.enclosing_class.index);
+                index.nested.entry(row.enclosing_class.index).or_default().push(row.nested_class.index);
+            }
+            index
+        })
+    }
+
+    /// Finds the *TypeDef* whose *MethodList* run owns the *MethodDef* row `rid`.
+    pub(crate) fn method_owner(&self, rid: u32) -> Option<&TypeDef> {
+        self.get_type_def(*self.member_owner_index().methods.get(&rid)?)
+    }
+
+    /// Finds the *TypeDef* whose *FieldList* run owns the *Field* row `rid`.
+    pub(crate) fn field_owner(&self, rid: u32) -> Option<&TypeDef> {
+        self.get_type_def(*self.member_owner_index().fields.get(&rid)?)
+    }
+
+    /// Indexes the method and field runs of every type once, so resolving the tokens of a body doesn't
+    /// scan the *TypeDef* table per token. A row claimed by more than one run belongs to the first type.
+    fn member_owner_index(&self) -> &MemberOwnerIndex {
+        self.member_owners.get_or_init(|| {
+            let mut index = MemberOwnerIndex::default();
+            for type_def in self.table::<TypeDef>() {
+                for rid in self.list_run(type_def, |type_def| type_def.method_list) {
+                    index.methods.entry(rid).or_insert(type_def.index);
+                }
+                for rid in self.list_run(type_def, |type_def| type_def.field_list) {
+                    index.fields.entry(rid).or_insert(type_def.index);
+                }
+            }
+            index
+        })
+    }
+
+    /// Whether any of the "#Strings", "#Blob" or "#GUID" heaps is indexed with 4 bytes.
+    pub fn uses_large_heaps(&self) -> bool {
+        let heap_sizes = self.streams.metadata.heap_sizes();
+        heap_sizes.is_large_strings() || heap_sizes.is_large_blob() || heap_sizes.is_large_guid()
+    }
+
+    /// Whether the image is a runnable executable: not a DLL, and with a managed entry point and a
+    /// console or GUI subsystem.
+    pub fn is_executable(&self) -> bool {
+        let characteristics = self.pe_header.characteristics;
+        let sub_system = self.optional_header.nt_specific_fields.sub_system;
+
+        !characteristics.is_dll()
+            && characteristics.contains(FileCharacteristics::IMAGE_FILE_EXECUTABLE_IMAGE)
+            && (sub_system == NtSpecificFields::IMAGE_SUBSYSTEM_WINDOWS_CUI || sub_system == NtSpecificFields::IMAGE_SUBSYSTEM_WINDOWS_GUI)
+            && self.entry_point_kind() != EntryPointKind::None
+    }
+
+    /// Interprets the *EntryPointToken* field of the CLI header, see [`EntryPointKind`].
+    pub fn entry_point_kind(&self) -> EntryPointKind {
+        match self.cli_header.entry_point_token {
+            MetadataToken::Table(TableKind::MethodDef, index) if index != 0 => EntryPointKind::MethodDef(index),
+            MetadataToken::Table(TableKind::File, index) if index != 0 => EntryPointKind::File(index),
+            _ => EntryPointKind::None,
+        }
+    }
+
+    /// The preferred address of the first byte of the image when loaded, see [`NtSpecificFields`].
+    pub fn image_base(&self) -> u32 {
+        self.optional_header.nt_specific_fields.image
+    }
+
+    /// The alignment of sections when loaded into memory, see [`NtSpecificFields`].
+    pub fn section_alignment(&self) -> u32 {
+        self.optional_header.nt_specific_fields.section_alignment
+    }
+
+    /// The alignment of section raw data in the file, see [`NtSpecificFields`].
+    pub fn file_alignment(&self) -> u32 {
+        self.optional_header.nt_specific_fields.file_alignment
+    }
+
+    /// The size of the image when loaded, including all headers, see [`NtSpecificFields`].
+    pub fn image_size(&self) -> u32 {
+        self.optional_header.nt_specific_fields.image_size
+    }
+
+    /// The combined size of the MS-DOS header, PE header, PE optional header and padding, see [`NtSpecificFields`].
+    pub fn header_size(&self) -> u32 {
+        self.optional_header.nt_specific_fields.header_size
+    }
+
+    /// Returns the section headers of the image, see [`SectionHeader`].
+    pub fn sections(&self) -> &[SectionHeader] {
+        self.buffer.sections()
+    }
+
+    /// Finds the section named `name`, e.g. `.text` or `.rsrc`.
+    pub fn section_by_name(&self, name: &str) -> Option<&SectionHeader> {
+        self.sections().iter().find(|section| section.name_str() == name)
+    }
+
+    /// Converts an RVA to a file offset, see [`PeParser::rva_to_offset`].
+    pub fn rva_to_offset(&self, rva: u32) -> Option<u64> {
+        self.buffer.rva_to_offset(rva)
+    }
+
+    /// Converts a file offset to an RVA, see [`PeParser::offset_to_rva`].
+    pub fn offset_to_rva(&self, offset: u64) -> Option<u32> {
+        self.buffer.offset_to_rva(offset)
+    }
+
+    /// Reads the array of [`VTableFixup`] entries pointed to by the *VTableFixups* field of the CLI header.
+    /// Returns an empty `Vec` if the image has no v-table fixups, or if the directory doesn't lie within any
+    /// section.
+    pub fn vtable_fixups(&self) -> Vec<VTableFixup> {
+        let directory = &self.cli_header.vtable_fixups;
+        if directory.size == 0 {
+            return Vec::new();
+        }
+
+        match self.buffer.get_rva_slice(directory.rva, directory.size) {
+            Some(slice) => slice
+                .chunks_exact(VTableFixup::SIZE)
+                .map(|chunk| VTableFixup::from(chunk.try_into().unwrap()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Computes the checksum of the image like `CheckSumMappedFile` of the Windows image helper library:
+    /// the one's complement sum of the file as 16-bit words, folded to 16 bits, plus the length of the
+    /// file. The *FileChecksum* field itself is summed as if it were zero.
+    pub fn compute_checksum(&self) -> u32 {
+        let data = self.buffer.bytes();
+        let checksum_offset = self.checksum_offset();
+
+        let mut sum: u32 = 0;
+        for (offset, word) in data.chunks(2).enumerate().map(|(i, word)| (i * 2, word)) {
+            if (checksum_offset..checksum_offset + 4).contains(&offset) {
+                continue;
+            }
+            let word = match word {
+                [low, high] => u16::from_le_bytes([*low, *high]),
+                [low] => *low as u16,
+                _ => unreachable!(),
+            };
+            sum += word as u32;
+            sum = (sum & 0xFFFF) + (sum >> 16);
+        }
+
+        sum + data.len() as u32
+    }
+
+    /// Whether *FileChecksum* matches [`PeImage::compute_checksum`]. The spec asks for a checksum of 0 in
+    /// CLI images, which is accepted as well since nothing verifies it.
+    pub fn verify_checksum(&self) -> bool {
+        let checksum = self.optional_header.nt_specific_fields.file_checksum;
+        checksum == 0 || checksum == self.compute_checksum()
+    }
+
+    /// The file offset of *FileChecksum*: `lfanew`, past the PE signature, the PE file header and the
+    /// first 64 bytes of the PE optional header.
+    fn checksum_offset(&self) -> usize {
+        let lfanew = self.buffer.bytes().get(0x3c..0x40)
+            .map_or(0, |lfanew| u32::from_le_bytes(lfanew.try_into().unwrap()));
+        lfanew as usize + 4 + 20 + 64
+    }
+
+    /// Returns the raw strong name signature pointed to by the *StrongNameSignature* field of the CLI header.
+    /// Returns `None` if the image isn't [`RuntimeFlags::COMIMAGE_FLAGS_STRONGNAMESIGNED`] or the directory is empty.
+    pub fn strong_name_signature(&self) -> Option<&[u8]> {
+        let directory = &self.cli_header.strong_name_signature;
+        if directory.size == 0 || !self.cli_header.flags.contains(RuntimeFlags::COMIMAGE_FLAGS_STRONGNAMESIGNED) {
+            return None;
+        }
+
+        self.buffer.get_rva_slice(directory.rva, directory.size)
+    }
+
+    /// Whether the image is strong name signed, i.e. flagged as [`RuntimeFlags::COMIMAGE_FLAGS_STRONGNAMESIGNED`]
+    /// with a signature present, see [`PeImage::strong_name_signature`]. A flagged image without a signature
+    /// is reported by [`PeImage::warnings`].
+    pub fn is_strong_named(&self) -> bool {
+        self.strong_name_signature().is_some()
+    }
+
+    /// # [II.22.18] FieldRVA : 0x1D
+    /// [...]
+    ///
+    /// Reads the initial value of a field with an RVA, e.g. the data of an array initializer used by
+    /// `RuntimeHelpers.InitializeArray`. The row doesn't record the size of the data, so `length` has to be
+    /// derived from the type of the field.
+    ///
+    /// [II.22.18]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=253
+    pub fn field_rva_data(&self, row: &FieldRVA, length: usize) -> Result<Vec<u8>, LushError> {
+        self.rva_tail(row.rva)?
+            .get(..length)
+            .map(<[u8]>::to_vec)
+            .ok_or(LushError::UnexpectedEof)
+    }
+
+    /// # [II.22.24] ManifestResource : 0x28
+    /// [...]
+    ///
+    /// Returns the data of an embedded resource, read from the *Resources* directory of the CLI header at
+    /// the row's *Offset*, where it's stored with a 4-byte length prefix. Returns `None` for a resource
+    /// stored in another file or assembly, i.e. when *Implementation* isn't null.
+    ///
+    /// [II.22.24]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=257
+    pub fn manifest_resource_data(&self, row: &ManifestResource) -> Result<Option<Vec<u8>>, LushError> {
+        if row.implementation.index != 0 {
+            return Ok(None);
+        }
+
+        let directory = &self.cli_header.resources;
+        let resources = self.buffer.get_rva_slice(directory.rva, directory.size)
+            .ok_or_else(|| LushError::InvalidBlob("Resources directory isn't backed by the file".to_string()))?;
+        let data = resources.get(row.offset as usize..).ok_or(LushError::UnexpectedEof)?;
+
+        let (length, data) = data.split_first_chunk::<4>().ok_or(LushError::UnexpectedEof)?;
+        let length = u32::from_le_bytes(*length) as usize;
+        data.get(..length)
+            .map(|data| Some(data.to_vec()))
+            .ok_or(LushError::UnexpectedEof)
+    }
+
+    /// Returns the bytes from `rva` up to the end of the file, see [`PeParser::get_rva_tail`].
+    pub(crate) fn rva_tail(&self, rva: u32) -> Result<&[u8], LushError> {
+        self.buffer.get_rva_tail(rva).ok_or_else(|| LushError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("RVA 0x{:08X} not found in any section", rva),
+        )))
+    }
+}
+
+/// Returns the row of the target table a pointer table row points at, see [`TableKind::pointer_table`].
+fn pointer_target(row: &Row) -> Option<u32> {
+    match row {
+        Row::FieldPtr(row) => Some(row.field.index),
+        Row::MethodPtr(row) => Some(row.method.index),
+        Row::ParamPtr(row) => Some(row.param.index),
+        Row::EventPtr(row) => Some(row.event.index),
+        Row::PropertyPtr(row) => Some(row.property.index),
+        _ => None,
+    }
+}
+
+/// Returns the run of rows of `table` whose key is `target`, by binary search. The rows must be ordered by
+/// `key`, as the tables flagged in the *Sorted* bit vector are by [`Row::sort_key`].
+pub fn find_range_by_key(table: &[Row], key: impl Fn(&Row) -> Option<u32>, target: u32) -> &[Row] {
+    let start = table.partition_point(|row| key(row) < Some(target));
+    let len = table[start..].partition_point(|row| key(row) == Some(target));
+    &table[start..start + len]
 }
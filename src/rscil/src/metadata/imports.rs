@@ -0,0 +1,111 @@
+use super::*;
+
+/// # II.25.3.1 Import Table and Import Address Table (IAT)
+///
+/// A module imported by the image, with the functions imported from it, see [`PeImage::imports`].
+///
+/// A CLI image imports a single function from `mscoree.dll`: `_CorExeMain` for an executable or
+/// `_CorDllMain` for a library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedModule {
+    pub name: String,
+    /// The names of the imported functions. Functions imported by ordinal are named `#ordinal`.
+    pub functions: Vec<String>,
+}
+
+/// The size of an entry of the Import Directory.
+const IMPORT_DIRECTORY_ENTRY_SIZE: usize = 20;
+/// Set in an Import Lookup Table entry of a PE32 image when the function is imported by ordinal.
+const IMPORT_BY_ORDINAL: u32 = 0x8000_0000;
+
+impl PeImage {
+    /// # II.25.3.1 Import Table and Import Address Table (IAT)
+    /// [...]
+    ///
+    /// Reads the Import Directory and follows the Import Lookup Table of each entry to the names of the
+    /// imported functions. Returns an empty list if the image has no Import Table.
+    pub fn imports(&self) -> Result<Vec<ImportedModule>, LushError> {
+        let directory = &self.optional_header.data_directories.import_table;
+        if directory.rva == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = self.rva_tail(directory.rva)?;
+        let mut modules = Vec::new();
+
+        // The directory is terminated by an entry that is all zeroes.
+        while let Some(entry) = entries.get(..IMPORT_DIRECTORY_ENTRY_SIZE) {
+            if entry.iter().all(|byte| *byte == 0) {
+                return Ok(modules);
+            }
+            entries = &entries[IMPORT_DIRECTORY_ENTRY_SIZE..];
+
+            let lookup_table = u32::from_le_bytes(entry[0..4].try_into().unwrap());
+            let name = u32::from_le_bytes(entry[12..16].try_into().unwrap());
+            let address_table = u32::from_le_bytes(entry[16..20].try_into().unwrap());
+
+            // Some linkers leave the lookup table out, the address table holds the same entries on disk.
+            let lookup_table = if lookup_table != 0 { lookup_table } else { address_table };
+
+            modules.push(ImportedModule {
+                name: self.read_c_string(name)?,
+                functions: self.imported_functions(lookup_table)?,
+            });
+        }
+
+        Err(LushError::UnexpectedEof)
+    }
+
+    fn imported_functions(&self, lookup_table: u32) -> Result<Vec<String>, LushError> {
+        let mut functions = Vec::new();
+        for entry in self.rva_tail(lookup_table)?.chunks_exact(4) {
+            let entry = u32::from_le_bytes(entry.try_into().unwrap());
+            match entry {
+                0 => return Ok(functions),
+                entry if entry & IMPORT_BY_ORDINAL != 0 => functions.push(format!("#{}", entry & 0xFFFF)),
+                // Hint/Name Table entry: a 2-byte hint followed by the null-terminated name.
+                entry => functions.push(self.read_c_string(entry + 2)?),
+            }
+        }
+        Err(LushError::UnexpectedEof)
+    }
+
+    fn read_c_string(&self, rva: u32) -> Result<String, LushError> {
+        let bytes = self.rva_tail(rva)?;
+        let length = bytes.iter().position(|byte| *byte == 0).ok_or(LushError::UnexpectedEof)?;
+        Ok(String::from_utf8_lossy(&bytes[..length]).into_owned())
+    }
+}
+
+/// The target of a P/Invoke method, as declared by `[DllImport]` in C#, see [`PeImage::pinvoke_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PInvokeInfo {
+    /// The name of the unmanaged module, e.g. `kernel32.dll`.
+    pub module: String,
+    /// The name of the function in the module.
+    pub entry_point: String,
+    pub charset: CharSet,
+    pub calling_convention: CallConv,
+}
+
+impl PeImage {
+    /// # [II.22.22] ImplMap : 0x1C
+    /// [...]
+    ///
+    /// Returns the unmanaged module and function `method` forwards to, from its *ImplMap* row and the
+    /// *ModuleRef* it imports from. `None` if the method has no *ImplMap* row, see [`MethodDef::is_pinvoke`].
+    ///
+    /// [II.22.22]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=256
+    pub fn pinvoke_info(&self, method: &MethodDef) -> Option<PInvokeInfo> {
+        let key = CodedIndex::from(TableKind::MethodDef, method.index).encode(CodedIndexTag::MemberForwarded)?;
+        let row = self.rows_by_key::<ImplMap>(key).first().copied()?;
+        let module = self.table::<ModuleRef>().get(row.import_scope.index)?;
+
+        Some(PInvokeInfo {
+            module: self.get_string(module.name).into_owned(),
+            entry_point: self.get_string(row.import_name).into_owned(),
+            charset: row.mapping_flags.char_set(),
+            calling_convention: row.mapping_flags.call_conv(),
+        })
+    }
+}
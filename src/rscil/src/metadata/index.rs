@@ -1,11 +1,12 @@
 
-use std::slice::Iter;
+use std::{fmt, slice::Iter, str::FromStr};
 
 use super::*;
 
 macro_rules! define_stream_index {
-    ($name:ident, $flag:path) => {
+    ($name:ident, $index_size:ident) => {
         #[derive(Debug, Clone, Copy, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
         pub struct $name(pub u32);
 
         impl From<u32> for $name {
@@ -22,13 +23,15 @@ macro_rules! define_stream_index {
             /// 
             /// * If e is an index into the GUID heap, 'blob', or String heap, it is stored using the number of bytes as defined in the HeapSizes field.
             fn decode(context: &TableDecodeContext, buffer: &mut Buffer) -> Result<$name, std::io::Error> {
-                if !context.heap_sizes.contains($flag) {
-                    return Ok($name::from(buffer.read_u16::<LittleEndian>()?));
-                }
-                else {
-                    return Ok($name::from(buffer.read_u32::<LittleEndian>()?));
+                match context.heap_sizes.$index_size() {
+                    4 => Ok($name::from(buffer.read_u32::<LittleEndian>()?)),
+                    _ => Ok($name::from(buffer.read_u16::<LittleEndian>()?)),
                 }
             }
+
+            fn size(context: &TableDecodeContext) -> usize {
+                context.heap_sizes.$index_size() as usize
+            }
         }
 
         impl From<u16> for $name {
@@ -40,9 +43,9 @@ macro_rules! define_stream_index {
     };
 }
 
-define_stream_index!(StringIndex, HeapSizes::STRING_FLAG);
-define_stream_index!(GuidIndex, HeapSizes::GUID_FLAG);
-define_stream_index!(BlobIndex, HeapSizes::BLOB_FLAG);
+define_stream_index!(StringIndex, string_index_size);
+define_stream_index!(GuidIndex, guid_index_size);
+define_stream_index!(BlobIndex, blob_index_size);
 
 macro_rules! define_coded_index_tag {
     {
@@ -75,7 +78,9 @@ macro_rules! define_coded_index_tag {
                 }
             }
 
-            pub fn get_table_kind(&self, data: u8) -> TableKind {
+            /// Returns the table encoded by the low tag bits of `data`, or
+            /// [`LushError::InvalidCodedIndexTag`] if the tag is reserved or unused.
+            pub fn get_table_kind(&self, data: u8) -> Result<TableKind, LushError> {
                 let data = data & ((1 << self.get_tag_size()) - 1);
 
                 match self {
@@ -83,16 +88,29 @@ macro_rules! define_coded_index_tag {
                         $name::$collection => {
                             match data {
                                 $(
-                                    $tag_num => TableKind::$tag,
+                                    $tag_num => Ok(TableKind::$tag),
                                 )*
-                                // TODO: Fix
-                                _ => TableKind::TypeDef,
+                                _ => Err(LushError::InvalidCodedIndexTag { tag: *self, value: data }),
                             }
                         },
                     )*
                 }
             }
 
+            /// Returns the tag encoding `table` in this kind of coded index, `None` if it can't point into `table`.
+            pub fn get_tag(&self, table: TableKind) -> Option<u8> {
+                match self {
+                    $(
+                        $name::$collection => match table {
+                            $(
+                                TableKind::$tag => Some($tag_num),
+                            )*
+                            _ => None,
+                        },
+                    )*
+                }
+            }
+
             /// # [II.24.2.6] #~ stream 
             /// 
             /// [...]
@@ -183,6 +201,7 @@ define_coded_index_tag!{
             InterfaceImpl : 5,
             MemberRef : 6,
             Module : 7,
+            DeclSecurity : 8,
             Property : 9,
             Event : 10,
             StandAloneSig : 11,
@@ -308,12 +327,18 @@ impl TableEnumDecode for CodedIndexTag {
         };
 
         let data = index >> self.get_tag_size();
-        let table = self.get_table_kind((index & 0xff) as u8);
+        let table = self.get_table_kind((index & 0xff) as u8)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
         Ok(CodedIndex::from(table, data))
     }
+
+    fn size(self, context: &TableDecodeContext) -> usize {
+        context.get_coded_index_size(self) as usize
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CodedIndex {
     pub table: TableKind,
     pub index: u32,
@@ -326,6 +351,13 @@ impl CodedIndex {
             index,
         }
     }
+
+    /// Encodes the index the way a column of the coded index kind `tag` stores it, the row index shifted
+    /// past the tag bits and or'ed with the tag of its table. `None` if `tag` can't point into the table.
+    pub fn encode(&self, tag: CodedIndexTag) -> Option<u32> {
+        let table = tag.get_tag(self.table)?;
+        Some(self.index << tag.get_tag_size() | table as u32)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -335,9 +367,45 @@ pub enum MetadataToken {
 }
 
 impl MetadataToken {
+    /// The largest row or heap index a token can hold, as it's stored in the lower 3 bytes.
+    pub const MAX_INDEX: u32 = 0x00FFFFFF;
+
+    /// Creates a token referencing row `rid` of the `kind` table.
+    ///
+    /// # Panics
+    ///
+    /// If `rid` doesn't fit in 24 bits, see [`MetadataToken::try_table`].
+    pub fn table(kind: TableKind, rid: u32) -> Self {
+        Self::try_table(kind, rid).unwrap_or_else(|| panic!("Row index out of range: 0x{:X}", rid))
+    }
+
+    /// Creates a token referencing row `rid` of the `kind` table, or `None` if `rid` doesn't fit in 24 bits.
+    pub fn try_table(kind: TableKind, rid: u32) -> Option<Self> {
+        (rid <= Self::MAX_INDEX).then_some(MetadataToken::Table(kind, rid))
+    }
+
+    /// Creates a token referencing the #US heap at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` doesn't fit in 24 bits, see [`MetadataToken::try_user_string`].
+    pub fn user_string(index: u32) -> Self {
+        Self::try_user_string(index).unwrap_or_else(|| panic!("User string index out of range: 0x{:X}", index))
+    }
+
+    /// Creates a token referencing the #US heap at `index`, or `None` if `index` doesn't fit in 24 bits.
+    pub fn try_user_string(index: u32) -> Option<Self> {
+        (index <= Self::MAX_INDEX).then_some(MetadataToken::UserString(index))
+    }
+
+    /// Creates the token referencing `row`, see [`Row::token`].
+    pub fn from_row(row: &Row) -> Self {
+        row.token()
+    }
+
     pub fn from_raw(raw: u32) -> Self {
         let table = (raw >> 24) as u8;
-        let index = raw & 0x00FFFFFF;
+        let index = raw & Self::MAX_INDEX;
 
         match table {
             0x70 => MetadataToken::UserString(index),
@@ -352,9 +420,61 @@ impl MetadataToken {
         }
     }
 
-    pub fn read(buffer: &mut Buffer) -> Result<Self, std::io::Error> {
+    pub fn read(buffer: &mut impl Read) -> Result<Self, std::io::Error> {
         let raw = buffer.read_u32::<LittleEndian>()?;
         Ok(MetadataToken::from_raw(raw))
     }
 }
 
+/// Renders the token in the canonical `0xTTRRRRRR` form used by ildasm, e.g. `0x06000001` or `0x70000001`.
+impl fmt::Display for MetadataToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:08X}", self.to_raw())
+    }
+}
+
+/// Serializes the token as its `0xTTRRRRRR` string, see the [`Display`](fmt::Display) implementation.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MetadataToken {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Parses either the `0xTTRRRRRR` hex form, where `TT` is the table number (or `0x70` for the #US heap) 
+/// and `RRRRRR` the row index, or the `Table:RID` form, e.g. `MethodDef:1` or `UserString:0x1`.
+impl FromStr for MetadataToken {
+    type Err = LushError;
+
+    fn from_str(s: &str) -> Result<Self, LushError> {
+        let invalid = || LushError::InvalidToken(s.to_string());
+
+        if let Some((table, rid)) = s.split_once(':') {
+            let rid = rid.trim();
+            let rid = match rid.strip_prefix("0x").or_else(|| rid.strip_prefix("0X")) {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => rid.parse::<u32>(),
+            }.map_err(|_| invalid())?;
+
+            return match table.trim() {
+                "UserString" => MetadataToken::try_user_string(rid).ok_or_else(invalid),
+                name => TableKind::from_name(name)
+                    .and_then(|kind| MetadataToken::try_table(kind, rid))
+                    .ok_or_else(invalid),
+            };
+        }
+
+        let hex = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).ok_or_else(invalid)?;
+        let raw = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+        let table = (raw >> 24) as u8;
+        let index = raw & Self::MAX_INDEX;
+
+        match table {
+            0x70 => Ok(MetadataToken::UserString(index)),
+            _ => TableKind::try_from_u8(table)
+                .map(|kind| MetadataToken::Table(kind, index))
+                .ok_or(LushError::InvalidTableKind(table)),
+        }
+    }
+}
+
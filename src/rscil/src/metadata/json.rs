@@ -0,0 +1,27 @@
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use super::*;
+
+/// The metadata tables of an image, serialized as a map from table name to its rows.
+struct Tables<'a>(&'a PeImage);
+
+impl Serialize for Tables<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let metadata = &self.0.streams.metadata;
+        let kinds = metadata.present_tables();
+
+        let mut map = serializer.serialize_map(Some(kinds.len()))?;
+        for kind in kinds {
+            map.serialize_entry(kind.name(), metadata.get_table(kind))?;
+        }
+        map.end()
+    }
+}
+
+impl PeImage {
+    /// Serializes every metadata table to JSON, as an object mapping each present table's name to the
+    /// array of its rows. Heap indices are kept as-is, they aren't resolved to their strings or blobs.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&Tables(self)).expect("metadata tables always serialize")
+    }
+}
@@ -10,6 +10,7 @@ macro_rules! table_kind_impl {
         )*
     }} => {
         #[$($attr)*]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize))]
         #[repr(u8)]
         $visibility enum $name {
             $(
@@ -18,13 +19,39 @@ macro_rules! table_kind_impl {
             )*
         }
 
-        impl From<u8> for $name {
-            fn from(value: u8) -> Self {
+        impl $name {
+            pub fn try_from_u8(value: u8) -> Option<Self> {
                 match value {
                     $(
-                        $value => $name::$field,
+                        $value => Some($name::$field),
+                    )*
+                    _ => None,
+                }
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        $name::$field => stringify!($field),
                     )*
-                    _ => panic!("Invalid table kind: {}", value),
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $(
+                        stringify!($field) => Some($name::$field),
+                    )*
+                    _ => None,
+                }
+            }
+        }
+
+        impl From<u8> for $name {
+            fn from(value: u8) -> Self {
+                match Self::try_from_u8(value) {
+                    Some(kind) => kind,
+                    None => panic!("Invalid table kind: {}", value),
                 }
             }
         }
@@ -49,24 +76,19 @@ table_kind_impl!{
         /// See [`Assembly`]
         Assembly = 0x20,
         /// # II.22.3 AssemblyOS : 0x22
-        /// [...]
-        /// 
-        /// It shall be ignored by the CLI. 
+        /// See [`AssemblyOS`]
         AssemblyOS = 0x22,
         /// # II.22.4 AssemblyProcessor : 0x21
-        /// [...]
-        /// It should be ignored by the CLI.
+        /// See [`AssemblyProcessor`]
         AssemblyProcessor = 0x21,
         /// # II.22.5 AssemblyRef : 0x23
         /// See [`AssemblyRef`]
         AssemblyRef = 0x23,
         /// # II.22.6 AssemblyRefOS : 0x25
-        /// [...]
-        /// They should be ignored by the CLI.
+        /// See [`AssemblyRefOS`]
         AssemblyRefOS = 0x25,
         /// # II.22.7 AssemblyRefProcessor : 0x24
-        /// [...]
-        /// They should be ignored by the CLI.
+        /// See [`AssemblyRefProcessor`]
         AssemblyRefProcessor = 0x24,
         /// # II.22.8 ClassLayout : 0x0F
         /// See [`ClassLayout`]
@@ -79,12 +101,21 @@ table_kind_impl!{
         /// # II.22.11 DeclSecurity : 0x0E
         /// See [`DeclSecurity`]
         DeclSecurity = 0x0E,
+        /// # ENCLog : 0x1E
+        /// See [`ENCLog`]
+        ENCLog = 0x1E,
+        /// # ENCMap : 0x1F
+        /// See [`ENCMap`]
+        ENCMap = 0x1F,
         /// # II.22.12 EventMap : 0x12
         /// See [`EventMap`]
         EventMap = 0x12,
         /// # II.22.13 Event : 0x14
         /// See [`Event`]
         Event = 0x14,
+        /// # EventPtr : 0x13
+        /// See [`EventPtr`]
+        EventPtr = 0x13,
         /// # II.22.14 ExportedType : 0x27
         /// See [`ExportedType`]
         ExportedType = 0x27,
@@ -97,6 +128,9 @@ table_kind_impl!{
         /// # II.22.17 FieldMarshal : 0x0D
         /// See [`FieldMarshal`]
         FieldMarshal = 0x0D,
+        /// # FieldPtr : 0x03
+        /// See [`FieldPtr`]
+        FieldPtr = 0x03,
         /// # II.22.18 FieldRVA : 0x1D
         /// See [`FieldRVA`]
         FieldRVA = 0x1D,
@@ -127,6 +161,9 @@ table_kind_impl!{
         /// # II.22.27 MethodImpl : 0x19
         /// See [`MethodImpl`]
         MethodImpl = 0x19,
+        /// # MethodPtr : 0x05
+        /// See [`MethodPtr`]
+        MethodPtr = 0x05,
         /// # II.22.28 MethodSemantics : 0x18
         /// See [`MethodSemantics`]
         MethodSemantics = 0x18,
@@ -145,12 +182,18 @@ table_kind_impl!{
         /// II.22.33 Param : 0x08
         /// See [`Param`]
         Param = 0x08,
+        /// # ParamPtr : 0x07
+        /// See [`ParamPtr`]
+        ParamPtr = 0x07,
         /// # II.22.34 Property : 0x17
         /// See [`Property`]
         Property = 0x17,
         /// # II.22.35 PropertyMap : 0x15
         /// See [`PropertyMap`]
         PropertyMap = 0x15,
+        /// # PropertyPtr : 0x16
+        /// See [`PropertyPtr`]
+        PropertyPtr = 0x16,
         /// # II.22.36 StandAloneSig : 0x11
         /// See [`StandAloneSig`]
         StandAloneSig = 0x11,
@@ -169,14 +212,33 @@ table_kind_impl!{
 impl TableKind {
     pub const NUM_TABLES: usize = 45;
 
-    pub fn from_bitmask(bitmask: u64) -> Vec<TableKind> {
+    /// Returns the pointer table indirecting the list columns that point into this table, e.g.
+    /// *FieldPtr* for *Field*, see [`FieldPtr`]. `None` for tables that aren't owned through a list column.
+    pub fn pointer_table(&self) -> Option<TableKind> {
+        match self {
+            TableKind::Field => Some(TableKind::FieldPtr),
+            TableKind::MethodDef => Some(TableKind::MethodPtr),
+            TableKind::Param => Some(TableKind::ParamPtr),
+            TableKind::Event => Some(TableKind::EventPtr),
+            TableKind::Property => Some(TableKind::PropertyPtr),
+            _ => None,
+        }
+    }
+
+    /// Returns the width in bytes of a row of this table: the sum of its columns, where constants have a
+    /// fixed width and heap, table and coded indices are 2 or 4 bytes wide depending on `context`.
+    pub fn row_size(&self, context: &TableDecodeContext) -> usize {
+        Row::size(*self, context)
+    }
+
+    pub fn from_bitmask(bitmask: u64) -> Result<Vec<TableKind>> {
         let mut kinds = Vec::new();
         for i in 0..64 {
             if (bitmask & (1 << i)) != 0 {
-                kinds.push(TableKind::from(i));
+                kinds.push(TableKind::try_from_u8(i).ok_or(LushError::InvalidTableKind(i))?);
             }
         }
-        kinds
+        Ok(kinds)
     }
 }
 
@@ -191,4 +253,8 @@ impl TableEnumDecode for TableKind {
             Ok(CodedIndex::from(self, buffer.read_u32::<LittleEndian>()?))
         }
     }
+
+    fn size(self, context: &TableDecodeContext) -> usize {
+        context.get_table_index_size(self) as usize
+    }
 }
\ No newline at end of file
@@ -0,0 +1,193 @@
+use super::*;
+
+/// # II.23.4 Marshalling descriptors
+///
+/// A Marshalling Descriptor is like a signature – it's a 'blob' of binary data. It describes how a field or
+/// parameter (which, as usual, covers the method return, as parameter number 0) should be marshalled when
+/// calling to or from unmanaged code via PInvoke dispatch.
+///
+/// Only the intrinsic types are listed by the spec, the remaining values are the ones emitted by the
+/// `MarshalAs` attribute of the .NET runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeType {
+    Boolean,
+    I1,
+    U1,
+    I2,
+    U2,
+    I4,
+    U4,
+    I8,
+    U8,
+    R4,
+    R8,
+    Currency,
+    BStr,
+    LPStr,
+    LPWStr,
+    LPTStr,
+    ByValTStr,
+    IUnknown,
+    IDispatch,
+    Struct,
+    Interface,
+    SafeArray,
+    ByValArray,
+    Int,
+    UInt,
+    VBByRefStr,
+    AnsiBStr,
+    TBStr,
+    VariantBool,
+    Func,
+    AsAny,
+    LPArray,
+    LPStruct,
+    CustomMarshaler,
+    Error,
+    IInspectable,
+    HString,
+    LPUTF8Str,
+    /// `NATIVE_TYPE_MAX`, used as the element type of an array to mean "not specified".
+    Max,
+    Unknown(u8),
+}
+
+impl From<u8> for NativeType {
+    fn from(value: u8) -> Self {
+        match value {
+            0x02 => NativeType::Boolean,
+            0x03 => NativeType::I1,
+            0x04 => NativeType::U1,
+            0x05 => NativeType::I2,
+            0x06 => NativeType::U2,
+            0x07 => NativeType::I4,
+            0x08 => NativeType::U4,
+            0x09 => NativeType::I8,
+            0x0a => NativeType::U8,
+            0x0b => NativeType::R4,
+            0x0c => NativeType::R8,
+            0x0f => NativeType::Currency,
+            0x13 => NativeType::BStr,
+            0x14 => NativeType::LPStr,
+            0x15 => NativeType::LPWStr,
+            0x16 => NativeType::LPTStr,
+            0x17 => NativeType::ByValTStr,
+            0x19 => NativeType::IUnknown,
+            0x1a => NativeType::IDispatch,
+            0x1b => NativeType::Struct,
+            0x1c => NativeType::Interface,
+            0x1d => NativeType::SafeArray,
+            0x1e => NativeType::ByValArray,
+            0x1f => NativeType::Int,
+            0x20 => NativeType::UInt,
+            0x22 => NativeType::VBByRefStr,
+            0x23 => NativeType::AnsiBStr,
+            0x24 => NativeType::TBStr,
+            0x25 => NativeType::VariantBool,
+            0x26 => NativeType::Func,
+            0x28 => NativeType::AsAny,
+            0x2a => NativeType::LPArray,
+            0x2b => NativeType::LPStruct,
+            0x2c => NativeType::CustomMarshaler,
+            0x2d => NativeType::Error,
+            0x2e => NativeType::IInspectable,
+            0x2f => NativeType::HString,
+            0x30 => NativeType::LPUTF8Str,
+            0x50 => NativeType::Max,
+            value => NativeType::Unknown(value),
+        }
+    }
+}
+
+/// A decoded *FieldMarshal.NativeType* blob, see [`decode_marshal_descriptor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarshalDescriptor {
+    /// A native type without any further data, e.g. [`NativeType::LPStr`] or [`NativeType::I4`].
+    Intrinsic(NativeType),
+    /// An inline fixed-length character array of `size` characters.
+    ByValTStr { size: u32 },
+    /// A COM `SAFEARRAY`, with the `VARENUM` of its elements if specified.
+    SafeArray { element_type: Option<u32> },
+    /// An inline fixed-length array of `size` elements.
+    ByValArray { size: u32, element_type: Option<NativeType> },
+    /// A pointer to an array. Its length is given by the parameter at `size_param_index` plus `size`.
+    LPArray { element_type: Option<NativeType>, size_param_index: Option<u32>, size: Option<u32> },
+}
+
+/// # II.23.4 Marshalling descriptors
+/// [...]
+///
+/// ```text
+/// MarshalSpec ::= NativeIntrinsic
+///               | ARRAY ArrayElemType
+///               | ARRAY ArrayElemType ParamNum
+///               | ARRAY ArrayElemType ParamNum NumElem
+/// ```
+///
+/// Decodes the common forms of a *FieldMarshal.NativeType* blob, other native types are returned as
+/// [`MarshalDescriptor::Intrinsic`] and any trailing data is ignored.
+pub fn decode_marshal_descriptor(blob: &[u8]) -> Result<MarshalDescriptor, LushError> {
+    let mut data = blob;
+    let native_type = NativeType::from(read_u8(&mut data)?);
+
+    let element_type = |data: &mut &[u8]| -> Result<Option<NativeType>, LushError> {
+        match data.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(NativeType::from(read_u8(data)?)).filter(|kind| *kind != NativeType::Max)),
+        }
+    };
+    let optional = |data: &mut &[u8]| -> Result<Option<u32>, LushError> {
+        match data.is_empty() {
+            true => Ok(None),
+            false => read_compressed(data).map(Some),
+        }
+    };
+
+    Ok(match native_type {
+        NativeType::ByValTStr => MarshalDescriptor::ByValTStr {
+            size: read_compressed(&mut data)?,
+        },
+        NativeType::SafeArray => MarshalDescriptor::SafeArray {
+            element_type: optional(&mut data)?,
+        },
+        NativeType::ByValArray => MarshalDescriptor::ByValArray {
+            size: read_compressed(&mut data)?,
+            element_type: element_type(&mut data)?,
+        },
+        NativeType::LPArray => MarshalDescriptor::LPArray {
+            element_type: element_type(&mut data)?,
+            size_param_index: optional(&mut data)?,
+            size: optional(&mut data)?,
+        },
+        native_type => MarshalDescriptor::Intrinsic(native_type),
+    })
+}
+
+impl PeImage {
+    /// Returns the marshalling descriptor of the field or parameter `parent`, see [`decode_marshal_descriptor`].
+    /// `None` if it has no *FieldMarshal* row.
+    pub fn marshal_of(&self, parent: CodedIndex) -> Result<Option<MarshalDescriptor>, LushError> {
+        let Some(row) = parent.encode(CodedIndexTag::HasFieldMarshal)
+            .and_then(|key| self.rows_by_key::<FieldMarshal>(key).first().copied()) else {
+            return Ok(None);
+        };
+
+        let blob = self.get_blob(row.native_type).ok_or(LushError::InvalidSignature(row.native_type.0))?;
+        decode_marshal_descriptor(blob).map(Some)
+    }
+}
+
+fn read_u8(data: &mut &[u8]) -> Result<u8, LushError> {
+    let (first, rest) = data.split_first()
+        .ok_or_else(|| LushError::InvalidBlob("unexpected end of marshalling descriptor".to_string()))?;
+    *data = rest;
+    Ok(*first)
+}
+
+/// Reads an unsigned integer compressed as described in §II.23.2, see [`read_compressed_u32`].
+fn read_compressed(data: &mut &[u8]) -> Result<u32, LushError> {
+    read_compressed_u32(data)
+        .map(|(value, _)| value)
+        .map_err(|error| LushError::InvalidBlob(format!("invalid marshalling descriptor: {}", error)))
+}
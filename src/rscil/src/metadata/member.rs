@@ -0,0 +1,56 @@
+use super::*;
+
+/// The two sorts of references combined in the *MemberRef* table, see [`MemberRef`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberKind {
+    /// A 'FieldRef'.
+    Field,
+    /// A 'MethodRef'.
+    Method,
+}
+
+/// A [`MemberRef`] with its declaring type and name resolved, see [`PeImage::member_ref_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberRefInfo {
+    /// The name of the declaring type, module or vararg method.
+    /// `None` if the parent is a *TypeSpec* or can't be resolved.
+    pub declaring_type: Option<String>,
+    pub name: String,
+    pub kind: MemberKind,
+}
+
+impl MemberRefInfo {
+    /// The leading byte of a FieldSig (§II.23.2.4), which tells a 'FieldRef' apart from a 'MethodRef'.
+    pub const FIELD: u8 = 0x06;
+}
+
+/// The members of a type visible outside its assembly, see [`PeImage::public_surface`].
+#[derive(Debug, Clone, Default)]
+pub struct TypeSurface<'a> {
+    pub methods: Vec<&'a MethodDef>,
+    pub fields: Vec<&'a Field>,
+    pub properties: Vec<&'a Property>,
+    pub events: Vec<&'a Event>,
+}
+
+/// The methods implementing a [`Property`], see [`PeImage::property_accessors`].
+#[derive(Debug, Clone, Default)]
+pub struct PropertyAccessors<'a> {
+    pub getter: Option<&'a MethodDef>,
+    pub setter: Option<&'a MethodDef>,
+    /// Methods flagged as `Other`.
+    pub others: Vec<&'a MethodDef>,
+}
+
+/// The methods implementing an [`Event`], see [`PeImage::event_accessors`].
+#[derive(Debug, Clone, Default)]
+pub struct EventAccessors<'a> {
+    /// The `add_` method.
+    pub add_on: Option<&'a MethodDef>,
+    /// The `remove_` method.
+    pub remove_on: Option<&'a MethodDef>,
+    /// The optional `raise_` method.
+    pub fire: Option<&'a MethodDef>,
+    /// Methods flagged as `Other`.
+    pub others: Vec<&'a MethodDef>,
+}
@@ -8,19 +8,56 @@ mod index;
 mod flags;
 mod cil;
 mod decode;
+mod error;
+mod view;
+mod member;
+mod dump;
+mod diff;
+mod marshal;
+mod options;
+mod validate;
+mod signatures;
+mod disasm;
+mod assembly;
+mod attribute;
+mod imports;
+mod stack;
+mod cfg;
+mod constant;
+mod format;
+#[cfg(feature = "rayon")]
+mod scan;
+#[cfg(feature = "serde")]
+mod json;
 
 use std::io::{Cursor, Read, Seek, SeekFrom};
 use byteorder::{LittleEndian, ReadBytesExt};
 
 pub use kind::TableKind;
 pub use headers::*;
-pub use streams::Streams;
-pub use image::PeImage;
+pub use streams::{format_guid, GuidStream, MetadataStream, Streams};
+pub use image::{find_range_by_key, PeImage};
 pub use parser::PeParser;
 pub use rows::*;
 pub use index::*;
 pub use flags::*;
 pub use cil::*;
 pub use decode::*;
+pub use error::{LushError, Result};
+pub use view::TableView;
+pub use member::*;
+pub(crate) use dump::DisplayColumn;
+pub use diff::{MetadataDiff, TableDiff};
+pub use options::ParseOptions;
+pub use validate::ParseWarning;
+pub use signatures::{CallingConvention, CustomMod, FieldSignature, LocalVarSig, MethodSignature, MethodSpecSignature, PropertySignature, SigType, StandaloneSignature};
+pub use assembly::{public_key_token, AssemblyName};
+pub use attribute::{AttributeArgument, CustomAttributeValue, NamedArgument, NamedArgumentKind, PermissionSet, SecurityAttribute};
+pub use imports::{ImportedModule, PInvokeInfo};
+pub use cfg::BasicBlock;
+pub use constant::ConstantValue;
+pub use marshal::{decode_marshal_descriptor, MarshalDescriptor, NativeType};
+#[cfg(feature = "rayon")]
+pub use scan::scan_directory;
 
 pub type Table = Vec<Row>;
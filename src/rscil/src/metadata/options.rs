@@ -0,0 +1,20 @@
+/// Options for [`PeParser::read_with`](super::PeParser::read_with).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Fail with [`LushError::SpecViolation`](super::LushError::SpecViolation) when the image violates a
+    /// "shall" rule of the spec that parsing could otherwise recover from.
+    pub strict: bool,
+    /// Parse every method body up front. When `false`, bodies are parsed on demand by
+    /// [`PeImage::get_method_body`](super::PeImage::get_method_body).
+    pub parse_method_bodies: bool,
+}
+
+impl Default for ParseOptions {
+    /// Lenient parsing of the whole image, the behaviour of [`PeParser::read`](super::PeParser::read).
+    fn default() -> Self {
+        ParseOptions {
+            strict: false,
+            parse_method_bodies: true,
+        }
+    }
+}
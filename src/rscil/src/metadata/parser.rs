@@ -3,10 +3,27 @@ use super::*;
 
 pub struct PeParser {
     filename : String,
-    buffer : Buffer,
+    buffer : Cursor<ImageData>,
     sections: Vec<SectionHeader>,
 }
 
+/// The bytes of the image file, either read into memory or mapped from the file, see [`PeParser::open_mmap`].
+enum ImageData {
+    Owned(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl AsRef<[u8]> for ImageData {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            ImageData::Owned(data) => data,
+            #[cfg(feature = "mmap")]
+            ImageData::Mapped(map) => map,
+        }
+    }
+}
+
 
 
 // II.25.2.1 MS-DOS header
@@ -38,29 +55,110 @@ static PE_SIGNATURE : [u8; 4] = [0x50, 0x45, 0x00, 0x00];
 
 impl PeParser {
     pub fn new(filename: &str, buffer: Cursor<Vec<u8>>) -> PeParser {
+        PeParser::with_data(filename, ImageData::Owned(buffer.into_inner()))
+    }
+
+    fn with_data(filename: &str, data: ImageData) -> PeParser {
         PeParser {
             filename: filename.to_string(),
-            buffer,
+            buffer: Cursor::new(data),
             sections: vec![]
         }
     }
 
+    /// Creates a parser over an image that is already in memory. `name` is only used as the image's filename.
+    pub fn from_bytes(name: &str, data: Vec<u8>) -> PeParser {
+        PeParser::new(name, Cursor::new(data))
+    }
+
+    /// Reads the whole image from `reader`, e.g. an embedded resource stream, see [`PeParser::from_bytes`].
+    pub fn from_reader<R: Read>(name: &str, reader: &mut R) -> Result<PeParser, std::io::Error> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        Ok(PeParser::from_bytes(name, data))
+    }
+
     pub fn open(filename: &str) -> Result<PeParser, std::io::Error> {
         let mut file = std::fs::File::open(filename)?;
         let mut data = Vec::new();
         file.read_to_end(&mut data)?;
 
-        Ok(PeParser::new(filename, Cursor::new(data)))
+        Ok(PeParser::from_bytes(filename, data))
+    }
+
+    /// Same as [`PeParser::open`], but maps the file into memory instead of reading it, which saves a copy
+    /// of large assemblies. The mapping is kept alive by the parser and the [`PeImage`] it reads. Heaps
+    /// and tables are still copied out of it while parsing.
+    ///
+    /// The file must not be modified or truncated while it is mapped: the changes would show through in
+    /// the parsed image, and reading a truncated part fails with a bus error.
+    #[cfg(feature = "mmap")]
+    pub fn open_mmap(filename: &str) -> Result<PeParser, std::io::Error> {
+        let file = std::fs::File::open(filename)?;
+        // SAFETY: the mapping is read-only, and the caller is responsible for not modifying the file while
+        // it is mapped, as documented above.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+
+        Ok(PeParser::with_data(filename, ImageData::Mapped(map)))
+    }
+
+    /// Same as [`PeParser::open`], but reads the file without blocking the async runtime. Parsing itself
+    /// is CPU-bound and stays synchronous, see [`PeParser::read`].
+    #[cfg(feature = "tokio")]
+    pub async fn open_async(filename: &str) -> Result<PeParser, std::io::Error> {
+        let data = tokio::fs::read(filename).await?;
+
+        Ok(PeParser::from_bytes(filename, data))
+    }
+
+    /// Parses the headers, streams and tables, and every method body up front.
+    /// See [`PeParser::read_metadata_only`] to skip the method bodies.
+    pub fn read(self) -> Result<PeImage, std::io::Error> {
+        let mut image = self.read_image()?;
+        image.warnings = image.collect_warnings();
+        image.load_method_bodies()?;
+        Ok(image)
+    }
+
+    /// Parses the headers, streams and tables, but no method bodies. Bodies are parsed on demand by
+    /// [`PeImage::get_method_body`], which saves most of the work when only names are needed.
+    pub fn read_metadata_only(self) -> Result<PeImage, LushError> {
+        self.read_with(ParseOptions {
+            parse_method_bodies: false,
+            ..ParseOptions::default()
+        })
+    }
+
+    /// Parses the image as configured by `options`, see [`ParseOptions`].
+    pub fn read_with(self, options: ParseOptions) -> Result<PeImage, LushError> {
+        let mut image = self.read_image()?;
+        image.warnings = image.collect_warnings();
+
+        if options.strict {
+            if let Some(ParseWarning::SpecViolation(violation)) = image.warnings.iter()
+                .find(|warning| matches!(warning, ParseWarning::SpecViolation(_)))
+            {
+                return Err(LushError::SpecViolation(violation.clone()));
+            }
+        }
+
+        if options.parse_method_bodies {
+            image.load_method_bodies()?;
+        }
+        Ok(image)
     }
 
-    pub fn read(mut self) -> Result<PeImage, std::io::Error> {
+    fn read_image(mut self) -> Result<PeImage, std::io::Error> {
         self.buffer.seek(SeekFrom::Start(0))?;
 
         self.read_dos_stub()?;
         let header = self.read_pe_header()?;
 
         // See Description of Machine field at II.25.2.2 PE file header
-        assert!(header.machine == 0x14c, "Invalid machine type");
+        if header.machine != 0x14c {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid machine type"));
+        }
 
         let optional_header = self.read_pe_optional_header(&header)?;
         let sections = self.read_section_header(&header)?;
@@ -68,12 +166,14 @@ impl PeParser {
         let cli_header = self.read_cli_header(&optional_header)?;
         let metadata_header = self.read_metadata_header(&cli_header)?;
         let streams = self.read_streams(
-            self.get_address(cli_header.meta_data.rva),
+            self.get_address(cli_header.meta_data.rva)?,
             &metadata_header.stream_headers
         )?;
 
         Ok(PeImage::new(
             self.filename.clone(),
+            header,
+            optional_header,
             cli_header,
             metadata_header,
             streams,
@@ -136,7 +236,7 @@ impl PeParser {
     /// # II.25.3.3 CLI header 
     /// See [`CliHeader`] struct for more information.
     fn read_cli_header(&mut self, optional_header: &PeOptionalHeader) -> Result<CliHeader, std::io::Error> {
-        self.seek_rva(optional_header.data_directories.cli_header.rva);
+        self.seek_rva(optional_header.data_directories.cli_header.rva)?;
         let mut buffer = [0u8; 72];
         self.buffer.read_exact(&mut buffer)?;
         Ok(CliHeader::from(&buffer))
@@ -145,7 +245,7 @@ impl PeParser {
     /// ## II.24.2.1 Metadata root
     /// See [`MetadataHeader`] struct for more information.
     fn read_metadata_header(&mut self, cli_header: &CliHeader) -> Result<MetadataHeader, std::io::Error> {
-        self.seek_rva(cli_header.meta_data.rva);
+        self.seek_rva(cli_header.meta_data.rva)?;
         MetadataHeader::from(&mut self.buffer)
     }
 
@@ -157,37 +257,49 @@ impl PeParser {
 
     /// # II.25.4 Common Intermediate Language physical layout
     /// See [`MethodBody`]
-    pub fn read_method_body(&mut self, rva: u32) -> Result<MethodBody, std::io::Error> {
-        let mut body = self.read_method_header(rva)?;
-
-        let start = self.get_position();
+    ///
+    /// Reads through its own cursor, so bodies can be parsed on demand from a shared [`PeImage`].
+    pub fn read_method_body(&self, rva: u32) -> Result<MethodBody, std::io::Error> {
+        let mut buffer = Cursor::new(self.buffer.get_ref().as_ref());
+        buffer.set_position(self.get_address(rva)?);
+        let (header, mut body) = Self::read_method_header(&mut buffer)?;
+
+        let start = buffer.position();
         let end = start + body.code_size as u64;
-        while self.get_position() < end {
+        while buffer.position() < end {
+            let offset = (buffer.position() - start) as u32;
             body.body.push(Instruction {
-                offset: (self.get_position() - start) as u32,
-                opcode: OpCode::parse(self.read_code()?, &mut self.buffer)?,
+                offset,
+                opcode: OpCode::parse(Self::read_code(&mut buffer, offset)?, &mut buffer)?,
             });
         }
+        if buffer.position() != end {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Last instruction overruns the code size"));
+        }
+        if header.has_more_sections() {
+            body.exception_clauses = MethodBody::read_sections(&mut buffer)?;
+        }
+        body.resolve_targets()?;
 
         Ok(body)
     }
 
     /// # II.25.4 Common Intermediate Language physical layout
     /// See [`MethodBody`]
-    fn read_method_header(&mut self, rva: u32) -> Result<MethodBody, std::io::Error> {
-        let position = self.seek_rva(rva);
-        let header = MethodHeaderType::new(self.buffer.read_u8()?);
+    fn read_method_header(buffer: &mut Cursor<&[u8]>) -> Result<(MethodHeaderType, MethodBody), std::io::Error> {
+        let position = buffer.position();
+        let header = MethodHeaderType::new(buffer.read_u8()?);
         if header.is_tiny_format() {
-            Ok(MethodBody::tiny(header.into()))
+            Ok((header, MethodBody::tiny(header.into())))
         }
         else if header.is_fat_format() {
-            self.buffer.set_position(position);
+            buffer.set_position(position);
             let mut bytes = [0u8; 12];
-            self.buffer.read_exact(&mut bytes)?;
-            Ok(MethodBody::fat(&bytes))
+            buffer.read_exact(&mut bytes)?;
+            Ok((header, MethodBody::fat(&bytes)))
         }
         else {
-            panic!("Invalid method header type");
+            Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid method header type"))
         }
     }
 
@@ -199,15 +311,15 @@ impl PeParser {
         self.buffer.seek(SeekFrom::Start(position)).unwrap();
     }
 
-    fn read_code(&mut self) -> Result<Code, std::io::Error> {
-        let mut op1 = self.buffer.read_u8()?;
-        let mut op2 = 0xff;
-        if op1 == 0xfe {
-            op2 = op1;
-            op1 = self.buffer.read_u8()?;
-        }
+    /// Reads a one-byte opcode, or a two-byte opcode starting with `0xFE`, see [`Code::from`]. An undefined
+    /// opcode fails with [`LushError::InvalidOpcode`] at the IL `offset` of the instruction.
+    pub(crate) fn read_code(buffer: &mut impl Read, offset: u32) -> Result<Code, std::io::Error> {
+        let bytes = match buffer.read_u8()? {
+            0xFE => [0xFE, buffer.read_u8()?],
+            op => [0xFF, op],
+        };
 
-        Ok(Code::from(&[op1, op2]))
+        Code::from(&bytes).ok_or_else(|| LushError::InvalidOpcode { bytes, offset }.into())
     }
 
     /// # [II.25] File format extensions to PE 
@@ -222,16 +334,59 @@ impl PeParser {
     /// lies, ie s  r < s+l. The file position of the item is then given by p+(r-s). 
     /// 
     /// [II.25]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=303
-    fn seek_rva(&mut self, rva: u32) -> u64 {
-        self.buffer.seek(SeekFrom::Start(self.get_address(rva))).unwrap()
+    fn seek_rva(&mut self, rva: u32) -> Result<u64, std::io::Error> {
+        let address = self.get_address(rva)?;
+        self.buffer.seek(SeekFrom::Start(address))
     }
 
-    fn get_address(&self, rva: u32) -> u64 {
+    fn get_address(&self, rva: u32) -> Result<u64, std::io::Error> {
+        self.rva_to_offset(rva).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, format!("RVA 0x{:08X} not found in any section", rva))
+        })
+    }
+
+    /// Converts an RVA to a file offset. Returns `None` if the RVA doesn't lie within any section.
+    pub fn rva_to_offset(&self, rva: u32) -> Option<u64> {
         for section in self.sections.iter() {
             if rva >= section.virtual_address && rva < section.virtual_address + section.virtual_size {
-                return section.pointer_to_raw_data as u64 + (rva - section.virtual_address) as u64;
+                return Some(section.pointer_to_raw_data as u64 + (rva - section.virtual_address) as u64);
             }
         }
-        panic!("RVA not found in any section");
+        None
+    }
+
+    /// Converts a file offset to an RVA, the inverse of [`PeParser::rva_to_offset`]. Returns `None` if the
+    /// offset doesn't lie within the raw data of any section, e.g. inside the headers.
+    pub fn offset_to_rva(&self, offset: u64) -> Option<u32> {
+        for section in self.sections.iter() {
+            let start = section.pointer_to_raw_data as u64;
+            if offset >= start && offset < start + section.size_of_raw_data as u64 {
+                return Some(section.virtual_address + (offset - start) as u32);
+            }
+        }
+        None
+    }
+
+    /// Returns the `size` bytes located at `rva`, or `None` if they aren't backed by the file.
+    pub fn get_rva_slice(&self, rva: u32, size: u32) -> Option<&[u8]> {
+        let start = self.rva_to_offset(rva)? as usize;
+        self.buffer.get_ref().as_ref().get(start..start + size as usize)
+    }
+
+    /// Returns the section headers of the image, in the order of the section table.
+    pub fn sections(&self) -> &[SectionHeader] {
+        &self.sections
+    }
+
+    /// Returns the whole image as read from the file.
+    pub fn bytes(&self) -> &[u8] {
+        self.buffer.get_ref().as_ref()
+    }
+
+    /// Returns the bytes from `rva` up to the end of the file, or `None` if `rva` isn't backed by the file.
+    /// Useful for structures whose length isn't known up front, like null-terminated strings.
+    pub fn get_rva_tail(&self, rva: u32) -> Option<&[u8]> {
+        let start = self.rva_to_offset(rva)? as usize;
+        self.buffer.get_ref().as_ref().get(start..)
     }
 }
@@ -20,6 +20,21 @@ macro_rules! cast_row {
     };
 }
 
+/// A concrete row type stored in the table of kind [`TableRow::KIND`].
+pub trait TableRow: Sized {
+    const KIND: TableKind;
+
+    fn from_row(row: &Row) -> Option<&Self>;
+
+    /// The 1-based index of the row in its table.
+    fn index(&self) -> u32;
+
+    /// The token referencing this row, see [`Row::token`].
+    fn token(&self) -> MetadataToken {
+        MetadataToken::Table(Self::KIND, self.index())
+    }
+}
+
 macro_rules! define_row {
     {$($path:path)*} => {
         
@@ -39,17 +54,51 @@ macro_rules! define_rows {
         })*
     }} => {
         #[$($attr)*]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize), serde(untagged))]
         $visibility enum $name {
             $($enum_name($enum_name),)*
         }
 
         impl $name {
-            pub fn read(buffer: &mut Buffer, kind: TableKind, context: &TableDecodeContext) -> Result<Self, std::io::Error> {
+            pub fn read(buffer: &mut Buffer, kind: TableKind, context: &TableDecodeContext) -> Result<Self> {
                 match kind {
                     $(
                         TableKind::$enum_name => Ok($name::$enum_name($enum_name::decode(context, buffer)?)),
                     )*
-                    _ => panic!("Invalid table kind: {:?}", kind)
+                }
+            }
+
+            pub fn index(&self) -> u32 {
+                match self {
+                    $($name::$enum_name(row) => row.index,)*
+                }
+            }
+
+            /// The kind of the table the row is stored in.
+            pub fn kind(&self) -> TableKind {
+                match self {
+                    $($name::$enum_name(_) => TableKind::$enum_name,)*
+                }
+            }
+
+            /// The token referencing this row, e.g. `0x06000001` for the first *MethodDef* row.
+            pub fn token(&self) -> MetadataToken {
+                MetadataToken::Table(self.kind(), self.index())
+            }
+
+            /// Returns the width in bytes of a row of the given kind, see [`TableKind::row_size`].
+            pub fn size(kind: TableKind, context: &TableDecodeContext) -> usize {
+                match kind {
+                    $(
+                        TableKind::$enum_name => <$enum_name as TableDecode>::size(context),
+                    )*
+                }
+            }
+
+            /// See the `columns` function of the concrete row.
+            pub fn columns(&self, image: &PeImage) -> Vec<(&'static str, String)> {
+                match self {
+                    $($name::$enum_name(row) => row.columns(image),)*
                 }
             }
         }
@@ -57,6 +106,7 @@ macro_rules! define_rows {
         $(
             $(#[$($enum_attr)*])*
             #[derive(Debug, Clone, Copy)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize))]
             $visibility struct $enum_name {
                 pub index: u32,
                 $($prop_vis $prop_name: define_rows!(@type $($prop_type)*),)*
@@ -69,6 +119,25 @@ macro_rules! define_rows {
                         _ => None
                     }
                 }
+
+                /// Returns each column's name and value, with indices resolved where cheap.
+                pub fn columns(&self, image: &PeImage) -> Vec<(&'static str, String)> {
+                    vec![
+                        $((stringify!($prop_name), DisplayColumn::display(&self.$prop_name, image)),)*
+                    ]
+                }
+            }
+
+            impl TableRow for $enum_name {
+                const KIND: TableKind = TableKind::$enum_name;
+
+                fn from_row(row: &$name) -> Option<&Self> {
+                    $enum_name::from_row(row)
+                }
+
+                fn index(&self) -> u32 {
+                    self.index
+                }
             }
 
             impl TableDecode for $enum_name {
@@ -80,6 +149,10 @@ macro_rules! define_rows {
                         $($prop_name: define_rows!(@decode $($prop_type)* [context, buffer]),)*
                     })
                 }
+
+                fn size(context: &TableDecodeContext) -> usize {
+                    0 $(+ define_rows!(@size $($prop_type)* [context]))*
+                }
             }
         )*
     };
@@ -95,6 +168,12 @@ macro_rules! define_rows {
     (@decode $type:ty [$context:expr, $buffer:expr]) => {
         <$type>::decode($context, $buffer)?
     };
+    (@size $enum:ident $name:ident [$context:expr]) => {
+        $enum::$name.size($context)
+    };
+    (@size $type:ty [$context:expr]) => {
+        <$type as TableDecode>::size($context)
+    };
 }
 
 
@@ -125,6 +204,32 @@ define_rows!{
             pub culture: StringIndex,
         }
 
+        /// # II.22.3 AssemblyOS : 0x22
+        /// 
+        /// The *AssemblyOS* table has the following columns: 
+        /// * *OSPlatformID* (a 4-byte constant)
+        /// * *OSMajorVersion* (a 4-byte constant)
+        /// * *OSMinorVersion* (a 4-byte constant)
+        /// 
+        /// This record should not be emitted into any PE file. However, if present in a PE file, it shall be 
+        /// treated as if all its fields were zero. It shall be ignored by the CLI. 
+        AssemblyOS {
+            pub os_platform_id: u32,
+            pub os_major: u32,
+            pub os_minor: u32,
+        }
+
+        /// # II.22.4 AssemblyProcessor : 0x21
+        /// 
+        /// The *AssemblyProcessor* table has the following column: 
+        /// * *Processor* (a 4-byte constant)
+        /// 
+        /// This record should not be emitted into any PE file. However, if present in a PE file, it should be 
+        /// treated as if its field were zero. It should be ignored by the CLI. 
+        AssemblyProcessor {
+            pub processor: u32,
+        }
+
         /// # II.22.8 AssemblyRef : 0x23
         /// 
         /// The *AssemblyRef* table has the following columns: 
@@ -148,6 +253,36 @@ define_rows!{
             pub hash_value: BlobIndex,
         }
 
+        /// # II.22.6 AssemblyRefOS : 0x25
+        /// 
+        /// The *AssemblyRefOS* table has the following columns: 
+        /// * *OSPlatformId* (a 4-byte constant)
+        /// * *OSMajorVersion* (a 4-byte constant)
+        /// * *OSMinorVersion* (a 4-byte constant)
+        /// * *AssemblyRef* (an index into the *AssemblyRef* table)
+        /// 
+        /// These records should not be emitted into any PE file. However, if present in a PE file, they should 
+        /// be treated as-if their fields were zero. They should be ignored by the CLI. 
+        AssemblyRefOS {
+            pub os_platform_id: u32,
+            pub os_major: u32,
+            pub os_minor: u32,
+            pub assembly_ref: TableKind::AssemblyRef,
+        }
+
+        /// # II.22.7 AssemblyRefProcessor : 0x24
+        /// 
+        /// The *AssemblyRefProcessor* table has the following columns: 
+        /// * *Processor* (a 4-byte constant)
+        /// * *AssemblyRef* (an index into the *AssemblyRef* table)
+        /// 
+        /// These records should not be emitted into any PE file. However, if present in a PE file, they should 
+        /// be treated as-if their fields were zero. They should be ignored by the CLI. 
+        AssemblyRefProcessor {
+            pub processor: u32,
+            pub assembly_ref: TableKind::AssemblyRef,
+        }
+
         /// # [II.22.8] ClassLayout : 0x0F
         /// 
         /// [...]
@@ -216,6 +351,25 @@ define_rows!{
             pub permission_set: BlobIndex,
         }
 
+        /// # ENCLog : 0x1E
+        ///
+        /// Not part of ECMA-335, the *ENCLog* table records the edits applied by edit-and-continue, one
+        /// row per changed row:
+        /// * *Token* (a 4-byte metadata token of the edited row)
+        /// * *FuncCode* (a 4-byte constant, the kind of edit)
+        ENCLog {
+            pub token: u32,
+            pub func_code: u32,
+        }
+
+        /// # ENCMap : 0x1F
+        ///
+        /// Not part of ECMA-335, the *ENCMap* table maps the rows of an edit-and-continue delta to tokens:
+        /// * *Token* (a 4-byte metadata token)
+        ENCMap {
+            pub token: u32,
+        }
+
         /// # [II.22.12] EventMap : 0x12
         /// 
         /// The *EventMap* table has the following columns:
@@ -248,6 +402,18 @@ define_rows!{
             pub event_type: CodedIndexTag::TypeDefOrRef,
         }
 
+        /// # EventPtr : 0x13
+        ///
+        /// Not part of ECMA-335, the *EventPtr* table only appears in uncompressed "#-" streams, where the
+        /// *Event* rows may be out of order. *EventMap.EventList* then indexes this table instead, and
+        /// its single column holds the actual row:
+        /// * *Event* (an index into the *Event* table)
+        ///
+        /// See [`TableKind::pointer_table`].
+        EventPtr {
+            pub event: TableKind::Event,
+        }
+
         /// # [II.22.14] ExportedType : 0x27 
         /// 
         /// [...]
@@ -321,6 +487,18 @@ define_rows!{
             pub native_type: BlobIndex,
         }
 
+        /// # FieldPtr : 0x03
+        ///
+        /// Not part of ECMA-335, the *FieldPtr* table only appears in uncompressed "#-" streams, where the
+        /// *Field* rows may be out of order. *TypeDef.FieldList* then indexes this table instead, and
+        /// its single column holds the actual row:
+        /// * *Field* (an index into the *Field* table)
+        ///
+        /// See [`TableKind::pointer_table`].
+        FieldPtr {
+            pub field: TableKind::Field,
+        }
+
         /// # [II.22.18] FieldRVA : 0x1D
         /// 
         /// The *FieldRVA* table has the following columns:
@@ -486,6 +664,18 @@ define_rows!{
             pub method_declaration: CodedIndexTag::MethodDefOrRef,
         }
 
+        /// # MethodPtr : 0x05
+        ///
+        /// Not part of ECMA-335, the *MethodPtr* table only appears in uncompressed "#-" streams, where the
+        /// *MethodDef* rows may be out of order. *TypeDef.MethodList* then indexes this table instead, and
+        /// its single column holds the actual row:
+        /// * *MethodDef* (an index into the *MethodDef* table)
+        ///
+        /// See [`TableKind::pointer_table`].
+        MethodPtr {
+            pub method: TableKind::MethodDef,
+        }
+
         /// # II.22.28] MethodSemantics : 0x18
         /// 
         /// The *MethodSemantics* table has the following columns: 
@@ -569,6 +759,18 @@ define_rows!{
             pub name: StringIndex,
         }
 
+        /// # ParamPtr : 0x07
+        ///
+        /// Not part of ECMA-335, the *ParamPtr* table only appears in uncompressed "#-" streams, where the
+        /// *Param* rows may be out of order. *MethodDef.ParamList* then indexes this table instead, and
+        /// its single column holds the actual row:
+        /// * *Param* (an index into the *Param* table)
+        ///
+        /// See [`TableKind::pointer_table`].
+        ParamPtr {
+            pub param: TableKind::Param,
+        }
+
         /// # [II.22.34] Property : 0x17
         /// 
         /// [...]
@@ -601,6 +803,18 @@ define_rows!{
             pub property_list: TableKind::Property,
         }
 
+        /// # PropertyPtr : 0x16
+        ///
+        /// Not part of ECMA-335, the *PropertyPtr* table only appears in uncompressed "#-" streams, where the
+        /// *Property* rows may be out of order. *PropertyMap.PropertyList* then indexes this table instead, and
+        /// its single column holds the actual row:
+        /// * *Property* (an index into the *Property* table)
+        ///
+        /// See [`TableKind::pointer_table`].
+        PropertyPtr {
+            pub property: TableKind::Property,
+        }
+
         /// # [II.22.36] StandAloneSig : 0x11
         /// 
         /// [...]
@@ -671,3 +885,96 @@ define_rows!{
     }
 }
 
+impl TypeDef {
+    /// See [`TypeAttributes::is_interface`].
+    pub fn is_interface(&self) -> bool {
+        self.flags.is_interface()
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.flags.is_abstract()
+    }
+
+    pub fn is_sealed(&self) -> bool {
+        self.flags.is_sealed()
+    }
+
+    pub fn visibility(&self) -> TypeVisibility {
+        self.flags.visibility()
+    }
+
+    pub fn layout(&self) -> Option<TypeLayout> {
+        self.flags.layout()
+    }
+}
+
+impl MethodDef {
+    pub fn is_static(&self) -> bool {
+        self.flags.contains(MethodAttributes::STATIC)
+    }
+
+    pub fn is_virtual(&self) -> bool {
+        self.flags.contains(MethodAttributes::VIRTUAL)
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.flags.contains(MethodAttributes::ABSTRACT)
+    }
+
+    /// Whether the method is implemented in unmanaged code through P/Invoke, see [`ImplMap`].
+    pub fn is_pinvoke(&self) -> bool {
+        self.flags.contains(MethodAttributes::PINVOKE_IMPL)
+    }
+
+    /// See [`MethodImplAttributes::code_type`].
+    pub fn code_type(&self) -> MethodCodeType {
+        self.impl_flags.code_type()
+    }
+}
+
+impl Row {
+    /// # [II.22] Metadata logical format: tables
+    /// [...]
+    ///
+    /// Returns the primary key the rows of a table flagged in the *Sorted* bit vector are ordered by,
+    /// `None` for tables that don't have one. Coded indices compare by their encoded value, see
+    /// [`CodedIndex::encode`], so e.g. custom attributes are grouped by parent table within each row index.
+    ///
+    /// | Table                    | Key column        |
+    /// | ------------------------ | ----------------- |
+    /// | *ClassLayout*            | *Parent*          |
+    /// | *Constant*               | *Parent*          |
+    /// | *CustomAttribute*        | *Parent*          |
+    /// | *DeclSecurity*           | *Parent*          |
+    /// | *FieldLayout*            | *Field*           |
+    /// | *FieldMarshal*           | *Parent*          |
+    /// | *FieldRVA*               | *Field*           |
+    /// | *GenericParam*           | *Owner*           |
+    /// | *GenericParamConstraint* | *Owner*           |
+    /// | *ImplMap*                | *MemberForwarded* |
+    /// | *InterfaceImpl*          | *Class*           |
+    /// | *MethodImpl*             | *Class*           |
+    /// | *MethodSemantics*        | *Association*     |
+    /// | *NestedClass*            | *NestedClass*     |
+    ///
+    /// [II.22]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=235
+    pub fn sort_key(&self) -> Option<u32> {
+        match self {
+            Row::ClassLayout(row) => Some(row.parent.index),
+            Row::Constant(row) => row.parent.encode(CodedIndexTag::HasConstant),
+            Row::CustomAttribute(row) => row.parent.encode(CodedIndexTag::HasCustomAttribute),
+            Row::DeclSecurity(row) => row.parent.encode(CodedIndexTag::HasDeclSecurity),
+            Row::FieldLayout(row) => Some(row.field.index),
+            Row::FieldMarshal(row) => row.parent.encode(CodedIndexTag::HasFieldMarshal),
+            Row::FieldRVA(row) => Some(row.field.index),
+            Row::GenericParam(row) => row.owner.encode(CodedIndexTag::TypeOrMethodDef),
+            Row::GenericParamConstraint(row) => Some(row.owner.index),
+            Row::ImplMap(row) => row.member_forwarded.encode(CodedIndexTag::MemberForwarded),
+            Row::InterfaceImpl(row) => Some(row.class.index),
+            Row::MethodImpl(row) => Some(row.class.index),
+            Row::MethodSemantics(row) => row.association.encode(CodedIndexTag::HasSemantics),
+            Row::NestedClass(row) => Some(row.nested_class.index),
+            _ => None,
+        }
+    }
+}
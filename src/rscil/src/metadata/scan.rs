@@ -0,0 +1,83 @@
+use std::{panic::{self, AssertUnwindSafe}, path::{Path, PathBuf}};
+
+use rayon::prelude::*;
+
+use super::*;
+
+/// Recursively finds every `*.dll` and `*.exe` below `path` and parses them in parallel, calling `callback`
+/// with each image that parsed successfully.
+/// 
+/// A file that can't be read or parsed doesn't stop the scan, instead its error is collected and returned
+/// together with its path. A panic while parsing a file or in `callback` is caught and collected as a
+/// [`LushError::Panic`] the same way. Symbolic links to directories aren't followed.
+pub fn scan_directory<F>(path: impl AsRef<Path>, callback: F) -> Vec<(PathBuf, LushError)>
+where
+    F: Fn(&PeImage) + Sync,
+{
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    collect_assemblies(path.as_ref(), &mut files, &mut errors);
+
+    let parse_errors: Vec<_> = files
+        .into_par_iter()
+        .filter_map(|file| {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                let data = std::fs::read(&file)?;
+                let image = PeParser::from_bytes(&file.to_string_lossy(), data).read()?;
+                callback(&image);
+                Ok(())
+            }));
+
+            match result {
+                Ok(Ok(())) => None,
+                Ok(Err(error)) => Some((file, error)),
+                Err(payload) => Some((file, LushError::Panic(panic_message(payload)))),
+            }
+        })
+        .collect();
+
+    errors.extend(parse_errors);
+    errors
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map_or("unknown panic", |message| message).to_string(),
+    }
+}
+
+fn collect_assemblies(path: &Path, files: &mut Vec<PathBuf>, errors: &mut Vec<(PathBuf, LushError)>) {
+    let entries = match std::fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(error) => {
+            errors.push((path.to_path_buf(), error.into()));
+            return;
+        }
+    };
+
+    for entry in entries {
+        // Symbolic links to directories aren't followed, so a link back up the tree can't loop.
+        let (path, is_dir) = match entry.and_then(|entry| Ok((entry.path(), entry.file_type()?.is_dir()))) {
+            Ok(entry) => entry,
+            Err(error) => {
+                errors.push((path.to_path_buf(), error.into()));
+                continue;
+            }
+        };
+
+        if is_dir {
+            collect_assemblies(&path, files, errors);
+        }
+        else if is_assembly(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn is_assembly(path: &Path) -> bool {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => extension.eq_ignore_ascii_case("dll") || extension.eq_ignore_ascii_case("exe"),
+        None => false,
+    }
+}
@@ -0,0 +1,472 @@
+use super::*;
+
+const ELEMENT_TYPE_VOID: u8 = 0x01;
+const ELEMENT_TYPE_BOOLEAN: u8 = 0x02;
+const ELEMENT_TYPE_CHAR: u8 = 0x03;
+const ELEMENT_TYPE_I1: u8 = 0x04;
+const ELEMENT_TYPE_U1: u8 = 0x05;
+const ELEMENT_TYPE_I2: u8 = 0x06;
+const ELEMENT_TYPE_U2: u8 = 0x07;
+const ELEMENT_TYPE_I4: u8 = 0x08;
+const ELEMENT_TYPE_U4: u8 = 0x09;
+const ELEMENT_TYPE_I8: u8 = 0x0a;
+const ELEMENT_TYPE_U8: u8 = 0x0b;
+const ELEMENT_TYPE_R4: u8 = 0x0c;
+const ELEMENT_TYPE_R8: u8 = 0x0d;
+const ELEMENT_TYPE_STRING: u8 = 0x0e;
+const ELEMENT_TYPE_PTR: u8 = 0x0f;
+const ELEMENT_TYPE_BYREF: u8 = 0x10;
+const ELEMENT_TYPE_VALUETYPE: u8 = 0x11;
+const ELEMENT_TYPE_CLASS: u8 = 0x12;
+const ELEMENT_TYPE_VAR: u8 = 0x13;
+const ELEMENT_TYPE_ARRAY: u8 = 0x14;
+const ELEMENT_TYPE_GENERICINST: u8 = 0x15;
+const ELEMENT_TYPE_TYPEDBYREF: u8 = 0x16;
+const ELEMENT_TYPE_I: u8 = 0x18;
+const ELEMENT_TYPE_U: u8 = 0x19;
+const ELEMENT_TYPE_FNPTR: u8 = 0x1b;
+const ELEMENT_TYPE_OBJECT: u8 = 0x1c;
+const ELEMENT_TYPE_SZARRAY: u8 = 0x1d;
+const ELEMENT_TYPE_MVAR: u8 = 0x1e;
+const ELEMENT_TYPE_CMOD_REQD: u8 = 0x1f;
+const ELEMENT_TYPE_CMOD_OPT: u8 = 0x20;
+const ELEMENT_TYPE_SENTINEL: u8 = 0x41;
+const ELEMENT_TYPE_PINNED: u8 = 0x45;
+
+const FIELD: u8 = 0x06;
+const LOCAL_SIG: u8 = 0x07;
+const PROPERTY: u8 = 0x08;
+const GENERICINST: u8 = 0x0a;
+
+const HASTHIS: u8 = 0x20;
+const EXPLICITTHIS: u8 = 0x40;
+const GENERIC: u8 = 0x10;
+
+/// # II.23.2.1 MethodDefSig
+/// [...]
+///
+/// The first byte of the Signature holds bits for HASTHIS, EXPLICITTHIS and calling convention (DEFAULT,
+/// VARARG, or GENERIC). These are ORed together.
+///
+/// The unmanaged calling conventions are only used by *StandAloneMethodSig*s, see §II.23.2.3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallingConvention {
+    Default,
+    C,
+    StdCall,
+    ThisCall,
+    FastCall,
+    VarArg,
+    Unknown(u8),
+}
+
+impl From<u8> for CallingConvention {
+    fn from(value: u8) -> Self {
+        match value & 0x0f {
+            0x0 => CallingConvention::Default,
+            0x1 => CallingConvention::C,
+            0x2 => CallingConvention::StdCall,
+            0x3 => CallingConvention::ThisCall,
+            0x4 => CallingConvention::FastCall,
+            0x5 => CallingConvention::VarArg,
+            value => CallingConvention::Unknown(value),
+        }
+    }
+}
+
+/// # II.23.1.16 Element types used in signatures
+///
+/// A decoded *Type* of a signature, see §II.23.2.12.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SigType {
+    Void,
+    Boolean,
+    Char,
+    I1,
+    U1,
+    I2,
+    U2,
+    I4,
+    U4,
+    I8,
+    U8,
+    R4,
+    R8,
+    I,
+    U,
+    String,
+    Object,
+    TypedByRef,
+    /// `CLASS TypeDefOrRefOrSpecEncoded`
+    Class(CodedIndex),
+    /// `VALUETYPE TypeDefOrRefOrSpecEncoded`
+    ValueType(CodedIndex),
+    /// `SZARRAY Type`, a single-dimensional, zero-based array.
+    SzArray(Box<SigType>),
+    /// `ARRAY Type ArrayShape`, a general array, see §II.23.2.13.
+    ///
+    /// `sizes` and `lo_bounds` hold the size and lower bound of the first dimensions, the remaining
+    /// dimensions are unspecified.
+    Array { element: Box<SigType>, rank: u32, sizes: Vec<u32>, lo_bounds: Vec<i32> },
+    /// `PTR Type`
+    Ptr(Box<SigType>),
+    /// `BYREF Type`
+    ByRef(Box<SigType>),
+    /// `VAR number`, a generic parameter of the enclosing type.
+    Var(u32),
+    /// `MVAR number`, a generic parameter of the method.
+    MVar(u32),
+    /// `PINNED Type`, only valid for local variables, see [`LocalVarSig`].
+    Pinned(Box<SigType>),
+    /// `GENERICINST (CLASS | VALUETYPE) TypeDefOrRefOrSpecEncoded GenArgCount Type*`, where `base` is the
+    /// [`SigType::Class`] or [`SigType::ValueType`] of the generic type.
+    GenericInst { base: Box<SigType>, args: Vec<SigType> },
+    /// `FNPTR MethodDefSig` or `FNPTR MethodRefSig`, a pointer to a function.
+    FnPtr(Box<MethodSignature>),
+    /// `CustomMod+ Type`, a type with custom modifiers. Only produced where the grammar allows modifiers:
+    /// the return and parameter types of a method, local variables and the element type of `PTR` and
+    /// `SZARRAY`. Fields keep their modifiers in [`FieldSignature::custom_modifiers`].
+    Modified { modifiers: Vec<CustomMod>, inner: Box<SigType> },
+}
+
+impl SigType {
+    /// Reads a *Type* from the start of `buffer` and advances it past the type. Indices of *TypeDef*,
+    /// *TypeRef* and *TypeSpec* rows are checked against the row counts of `context`.
+    pub fn parse(buffer: &mut &[u8], context: &TableDecodeContext) -> Result<SigType, std::io::Error> {
+        let element_type = buffer.read_u8()?;
+
+        Ok(match element_type {
+            ELEMENT_TYPE_VOID => SigType::Void,
+            ELEMENT_TYPE_BOOLEAN => SigType::Boolean,
+            ELEMENT_TYPE_CHAR => SigType::Char,
+            ELEMENT_TYPE_I1 => SigType::I1,
+            ELEMENT_TYPE_U1 => SigType::U1,
+            ELEMENT_TYPE_I2 => SigType::I2,
+            ELEMENT_TYPE_U2 => SigType::U2,
+            ELEMENT_TYPE_I4 => SigType::I4,
+            ELEMENT_TYPE_U4 => SigType::U4,
+            ELEMENT_TYPE_I8 => SigType::I8,
+            ELEMENT_TYPE_U8 => SigType::U8,
+            ELEMENT_TYPE_R4 => SigType::R4,
+            ELEMENT_TYPE_R8 => SigType::R8,
+            ELEMENT_TYPE_I => SigType::I,
+            ELEMENT_TYPE_U => SigType::U,
+            ELEMENT_TYPE_STRING => SigType::String,
+            ELEMENT_TYPE_OBJECT => SigType::Object,
+            ELEMENT_TYPE_TYPEDBYREF => SigType::TypedByRef,
+            ELEMENT_TYPE_CLASS => SigType::Class(read_type_def_or_ref(buffer, context)?),
+            ELEMENT_TYPE_VALUETYPE => SigType::ValueType(read_type_def_or_ref(buffer, context)?),
+            ELEMENT_TYPE_SZARRAY => SigType::SzArray(Box::new(SigType::parse_modified(buffer, context)?)),
+            ELEMENT_TYPE_PTR => SigType::Ptr(Box::new(SigType::parse_modified(buffer, context)?)),
+            ELEMENT_TYPE_BYREF => SigType::ByRef(Box::new(SigType::parse(buffer, context)?)),
+            ELEMENT_TYPE_VAR => SigType::Var(read_compressed_u32(buffer)?.0),
+            ELEMENT_TYPE_MVAR => SigType::MVar(read_compressed_u32(buffer)?.0),
+            ELEMENT_TYPE_ARRAY => {
+                let element = Box::new(SigType::parse(buffer, context)?);
+                let (rank, _) = read_compressed_u32(buffer)?;
+                let (size_count, _) = read_compressed_u32(buffer)?;
+                let sizes = (0..size_count)
+                    .map(|_| read_compressed_u32(buffer).map(|(size, _)| size))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let (lo_bound_count, _) = read_compressed_u32(buffer)?;
+                let lo_bounds = (0..lo_bound_count)
+                    .map(|_| read_compressed_i32(buffer).map(|(lo_bound, _)| lo_bound))
+                    .collect::<Result<Vec<_>, _>>()?;
+                SigType::Array { element, rank, sizes, lo_bounds }
+            }
+            ELEMENT_TYPE_GENERICINST => {
+                let base = match buffer.read_u8()? {
+                    ELEMENT_TYPE_CLASS => SigType::Class(read_type_def_or_ref(buffer, context)?),
+                    ELEMENT_TYPE_VALUETYPE => SigType::ValueType(read_type_def_or_ref(buffer, context)?),
+                    other => return Err(invalid_signature(format!("Expected CLASS or VALUETYPE after GENERICINST, found 0x{:02X}", other))),
+                };
+                let (arg_count, _) = read_compressed_u32(buffer)?;
+                let args = (0..arg_count)
+                    .map(|_| SigType::parse(buffer, context))
+                    .collect::<Result<Vec<_>, _>>()?;
+                SigType::GenericInst { base: Box::new(base), args }
+            }
+            ELEMENT_TYPE_FNPTR => SigType::FnPtr(Box::new(MethodSignature::read(buffer, context)?)),
+            other => return Err(invalid_signature(format!("Unsupported element type 0x{:02X}", other))),
+        })
+    }
+
+    /// Reads a *Type* preceded by any number of custom modifiers, see [`SigType::Modified`].
+    fn parse_modified(buffer: &mut &[u8], context: &TableDecodeContext) -> Result<SigType, std::io::Error> {
+        let modifiers = CustomMod::parse_all(buffer, context)?;
+        let sig_type = SigType::parse(buffer, context)?;
+
+        Ok(match modifiers.is_empty() {
+            true => sig_type,
+            false => SigType::Modified { modifiers, inner: Box::new(sig_type) },
+        })
+    }
+}
+
+/// # II.23.2.1 MethodDefSig
+/// [...]
+///
+/// ```text
+/// MethodDefSig ::= [[HASTHIS] [EXPLICITTHIS]] (DEFAULT | VARARG | GENERIC GenParamCount) ParamCount RetType Param*
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodSignature {
+    pub has_this: bool,
+    pub explicit_this: bool,
+    pub calling_convention: CallingConvention,
+    pub generic_param_count: u32,
+    pub return_type: SigType,
+    pub params: Vec<SigType>,
+}
+
+impl MethodSignature {
+    pub fn parse(blob: &[u8], context: &TableDecodeContext) -> Result<MethodSignature, std::io::Error> {
+        let mut buffer = blob;
+        MethodSignature::read(&mut buffer, context)
+    }
+
+    /// Reads a method signature from the start of `buffer`, as embedded in a [`SigType::FnPtr`].
+    fn read(buffer: &mut &[u8], context: &TableDecodeContext) -> Result<MethodSignature, std::io::Error> {
+        let flags = buffer.read_u8()?;
+
+        let calling_convention = CallingConvention::from(flags);
+        if let CallingConvention::Unknown(value) = calling_convention {
+            return Err(invalid_signature(format!("0x{:02X} is not a method calling convention", value)));
+        }
+
+        let generic_param_count = match flags & GENERIC != 0 {
+            true => read_compressed_u32(buffer)?.0,
+            false => 0,
+        };
+        let (param_count, _) = read_compressed_u32(buffer)?;
+        let return_type = SigType::parse_modified(buffer, context)?;
+
+        // Every parameter takes at least a byte, so a bogus count can't reserve more than the blob holds.
+        let mut params = Vec::with_capacity((param_count as usize).min(buffer.len()));
+        for _ in 0..param_count {
+            // The variable arguments of a VARARG call site are preceded by a SENTINEL.
+            if buffer.first() == Some(&ELEMENT_TYPE_SENTINEL) {
+                *buffer = &buffer[1..];
+            }
+            params.push(SigType::parse_modified(buffer, context)?);
+        }
+
+        Ok(MethodSignature {
+            has_this: flags & HASTHIS != 0,
+            explicit_this: flags & EXPLICITTHIS != 0,
+            calling_convention,
+            generic_param_count,
+            return_type,
+            params,
+        })
+    }
+}
+
+/// # II.23.2.7 CustomMod
+/// [...]
+///
+/// ```text
+/// CustomMod ::= (CMOD_OPT | CMOD_REQD) TypeDefOrRefOrSpecEncoded
+/// ```
+///
+/// The CMOD_OPT or CMOD_REQD value is compressed, see §II.23.2. The CMOD_OPT or CMOD_REQD is followed
+/// by a metadata token that indexes a row in the TypeDef table or the TypeRef table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CustomMod {
+    /// `CMOD_REQD` rather than `CMOD_OPT`, a required modifier that callers must understand.
+    pub required: bool,
+    /// The *TypeDef* or *TypeRef* of the modifier, e.g. `System.Runtime.CompilerServices.IsVolatile`.
+    pub type_ref: CodedIndex,
+}
+
+impl CustomMod {
+    /// Reads the custom modifiers at the start of `buffer`, if any.
+    fn parse_all(buffer: &mut &[u8], context: &TableDecodeContext) -> Result<Vec<CustomMod>, std::io::Error> {
+        let mut modifiers = Vec::new();
+        while let Some(&element_type @ (ELEMENT_TYPE_CMOD_REQD | ELEMENT_TYPE_CMOD_OPT)) = buffer.first() {
+            *buffer = &buffer[1..];
+            modifiers.push(CustomMod {
+                required: element_type == ELEMENT_TYPE_CMOD_REQD,
+                type_ref: read_type_def_or_ref(buffer, context)?,
+            });
+        }
+        Ok(modifiers)
+    }
+}
+
+/// # II.23.2.4 FieldSig
+/// [...]
+///
+/// ```text
+/// FieldSig ::= FIELD CustomMod* Type
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSignature {
+    pub custom_modifiers: Vec<CustomMod>,
+    pub field_type: SigType,
+}
+
+impl FieldSignature {
+    pub fn parse(blob: &[u8], context: &TableDecodeContext) -> Result<FieldSignature, std::io::Error> {
+        let mut buffer = blob;
+        let kind = buffer.read_u8()?;
+        if kind != FIELD {
+            return Err(invalid_signature(format!("Expected FIELD, found 0x{:02X}", kind)));
+        }
+
+        let custom_modifiers = CustomMod::parse_all(&mut buffer, context)?;
+        let field_type = SigType::parse(&mut buffer, context)?;
+
+        Ok(FieldSignature { custom_modifiers, field_type })
+    }
+}
+
+/// # II.23.2.5 PropertySig
+/// [...]
+///
+/// ```text
+/// PropertySig ::= PROPERTY [HASTHIS] ParamCount CustomMod* Type Param*
+/// ```
+///
+/// The *Param*s are the index parameters of an indexer, e.g. `this[int]`, and are empty for any other
+/// property.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertySignature {
+    pub has_this: bool,
+    pub property_type: SigType,
+    pub index_params: Vec<SigType>,
+}
+
+impl PropertySignature {
+    pub fn parse(blob: &[u8], context: &TableDecodeContext) -> Result<PropertySignature, std::io::Error> {
+        let mut buffer = blob;
+        let flags = buffer.read_u8()?;
+        if flags & !HASTHIS != PROPERTY {
+            return Err(invalid_signature(format!("Expected PROPERTY, found 0x{:02X}", flags)));
+        }
+
+        let (param_count, _) = read_compressed_u32(&mut buffer)?;
+        let property_type = SigType::parse_modified(&mut buffer, context)?;
+        let index_params = (0..param_count)
+            .map(|_| SigType::parse_modified(&mut buffer, context))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PropertySignature { has_this: flags & HASTHIS != 0, property_type, index_params })
+    }
+}
+
+/// # II.23.2.6 LocalVarSig
+/// [...]
+///
+/// ```text
+/// LocalVarSig ::= LOCAL_SIG Count (TYPEDBYREF | ([CustomMod] [Constraint]) [BYREF] Type)+
+/// ```
+///
+/// The only *Constraint* is `PINNED`, which is kept as [`SigType::Pinned`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalVarSig {
+    pub locals: Vec<SigType>,
+}
+
+impl LocalVarSig {
+    pub fn parse(blob: &[u8], context: &TableDecodeContext) -> Result<LocalVarSig, std::io::Error> {
+        let mut buffer = blob;
+        let kind = buffer.read_u8()?;
+        if kind != LOCAL_SIG {
+            return Err(invalid_signature(format!("Expected LOCAL_SIG, found 0x{:02X}", kind)));
+        }
+
+        let (count, _) = read_compressed_u32(&mut buffer)?;
+        // Every local takes at least a byte, so a bogus count can't reserve more than the blob holds.
+        let mut locals = Vec::with_capacity((count as usize).min(buffer.len()));
+        for _ in 0..count {
+            let modifiers = CustomMod::parse_all(&mut buffer, context)?;
+            let mut local = match buffer.first() {
+                Some(&ELEMENT_TYPE_PINNED) => {
+                    buffer = &buffer[1..];
+                    SigType::Pinned(Box::new(SigType::parse(&mut buffer, context)?))
+                }
+                _ => SigType::parse(&mut buffer, context)?,
+            };
+            if !modifiers.is_empty() {
+                local = SigType::Modified { modifiers, inner: Box::new(local) };
+            }
+            locals.push(local);
+        }
+
+        Ok(LocalVarSig { locals })
+    }
+}
+
+/// # II.22.36 StandAloneSig
+/// [...]
+///
+/// The *Signature* blob of a *StandAloneSig* row, either the local variables of a method body or the
+/// call site signature of a `calli` instruction (§II.23.2.3), told apart by their leading byte.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StandaloneSignature {
+    LocalVars(Vec<SigType>),
+    Method(MethodSignature),
+}
+
+impl StandaloneSignature {
+    pub fn parse(blob: &[u8], context: &TableDecodeContext) -> Result<StandaloneSignature, std::io::Error> {
+        match blob.first() {
+            Some(&LOCAL_SIG) => LocalVarSig::parse(blob, context).map(|signature| StandaloneSignature::LocalVars(signature.locals)),
+            _ => MethodSignature::parse(blob, context).map(StandaloneSignature::Method),
+        }
+    }
+}
+
+/// # II.23.2.15 MethodSpec
+/// [...]
+///
+/// ```text
+/// MethodSpecBlob ::= GENRICINST GenArgCount Type+
+/// ```
+///
+/// The type arguments of an instantiated generic method, see [`MethodSpec`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodSpecSignature {
+    pub args: Vec<SigType>,
+}
+
+impl MethodSpecSignature {
+    pub fn parse(blob: &[u8], context: &TableDecodeContext) -> Result<MethodSpecSignature, std::io::Error> {
+        let mut buffer = blob;
+        let kind = buffer.read_u8()?;
+        if kind != GENERICINST {
+            return Err(invalid_signature(format!("Expected GENERICINST, found 0x{:02X}", kind)));
+        }
+
+        let (arg_count, _) = read_compressed_u32(&mut buffer)?;
+        let args = (0..arg_count)
+            .map(|_| SigType::parse(&mut buffer, context))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(MethodSpecSignature { args })
+    }
+}
+
+/// # II.23.2.8 TypeDefOrRefOrSpecEncoded
+/// [...]
+///
+/// These items are compact ways to store a TypeDef, TypeRef, or TypeSpec token in a Signature.
+///
+/// The encoded version of the token is computed by shifting the row index left by 2 bits and ORing in the
+/// table tag, before compressing the result like any other unsigned integer.
+fn read_type_def_or_ref(buffer: &mut &[u8], context: &TableDecodeContext) -> Result<CodedIndex, std::io::Error> {
+    let (value, _) = read_compressed_u32(buffer)?;
+    let table = CodedIndexTag::TypeDefOrRef.get_table_kind((value & 0b11) as u8)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+    let index = value >> 2;
+
+    if index == 0 || index > context.get_row_count(table) {
+        return Err(invalid_signature(format!("{:?} index {} is out of range", table, index)));
+    }
+    Ok(CodedIndex::from(table, index))
+}
+
+fn invalid_signature(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message)
+}
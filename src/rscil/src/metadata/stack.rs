@@ -0,0 +1,115 @@
+use super::*;
+
+impl StackBehaviour {
+    /// The number of items popped off the evaluation stack, `None` for [`StackBehaviour::VarPop`] which
+    /// depends on the signature of the called method. Push behaviours pop nothing.
+    pub fn pop_count(&self) -> Option<u8> {
+        match self {
+            StackBehaviour::VarPop => None,
+            StackBehaviour::Pop1 | StackBehaviour::PopI | StackBehaviour::PopRef => Some(1),
+            StackBehaviour::Pop1Pop1
+            | StackBehaviour::PopIPop1
+            | StackBehaviour::PopIPopI
+            | StackBehaviour::PopIPopI8
+            | StackBehaviour::PopIPopR4
+            | StackBehaviour::PopIPopR8
+            | StackBehaviour::PopRefPopI => Some(2),
+            StackBehaviour::PopIPopIPopI
+            | StackBehaviour::PopRefPopIPopI
+            | StackBehaviour::PopRefPopIPopI8
+            | StackBehaviour::PopRefPopIPopR4
+            | StackBehaviour::PopRefPopIPopR8
+            | StackBehaviour::PopRefPopIPopRef => Some(3),
+            _ => Some(0),
+        }
+    }
+
+    /// The number of items pushed onto the evaluation stack, `None` for [`StackBehaviour::VarPush`] which
+    /// depends on the signature of the called method. Pop behaviours push nothing.
+    pub fn push_count(&self) -> Option<u8> {
+        match self {
+            StackBehaviour::VarPush => None,
+            StackBehaviour::Push1
+            | StackBehaviour::PushI
+            | StackBehaviour::PushI8
+            | StackBehaviour::PushR4
+            | StackBehaviour::PushR8
+            | StackBehaviour::PushRef => Some(1),
+            StackBehaviour::Push1Push1 => Some(2),
+            _ => Some(0),
+        }
+    }
+}
+
+impl Instruction {
+    /// Returns the net change in evaluation stack depth caused by the instruction, pushes minus pops.
+    ///
+    /// The variable behaviours are resolved through signatures: `call`, `callvirt`, `newobj` and `calli`
+    /// pop their arguments (and `this`, or the function pointer of `calli`) and push the return value if
+    /// it isn't `void`, while `ret` pops the return value of `method`, the method whose body holds the
+    /// instruction. Fails if the signature can't be resolved or decoded.
+    pub fn stack_delta(&self, image: &PeImage, method: &MethodDef) -> Result<i32, LushError> {
+        let code = self.opcode.code();
+        let metadata = code.metadata();
+        let pushes = metadata.stack_behaviour_push.push_count();
+        if let (Some(pops), Some(pushes)) = (metadata.stack_behaviour_pop.pop_count(), pushes) {
+            return Ok(pushes as i32 - pops as i32);
+        }
+
+        let signature = match self.opcode.operand() {
+            Operand::Token(token) => image.call_site_signature(token)?,
+            // ret is the only variable instruction without a token.
+            _ => return Ok(-(returns_value(&image.get_method_signature(method)?.return_type) as i32)),
+        };
+
+        // With EXPLICITTHIS the type of `this` is already the first parameter.
+        let arguments = signature.params.len() as i32;
+        let this = (signature.has_this && !signature.explicit_this) as i32;
+        let pops = match code {
+            // newobj creates `this` rather than popping it.
+            Code::Newobj => arguments,
+            // calli pops the function pointer after the arguments.
+            Code::Calli => arguments + this + 1,
+            _ => arguments + this,
+        };
+        let pushes = pushes.map_or_else(|| returns_value(&signature.return_type) as i32, |pushes| pushes as i32);
+
+        Ok(pushes - pops)
+    }
+}
+
+impl PeImage {
+    /// Decodes the signature of the method a call instruction's token refers to: the *MethodDef* or
+    /// *MemberRef* (through a *MethodSpec* for generic instantiations), or the *StandAloneSig* of `calli`.
+    fn call_site_signature(&self, token: MetadataToken) -> Result<MethodSignature, LushError> {
+        let invalid = || LushError::InvalidToken(token.to_string());
+
+        let signature = match token {
+            MetadataToken::Table(TableKind::MethodDef, rid) => self.get_method_def(rid).map(|method| method.signature),
+            MetadataToken::Table(TableKind::MemberRef, rid) => self.get_member_ref(rid).map(|member_ref| member_ref.signature),
+            MetadataToken::Table(TableKind::MethodSpec, rid) => {
+                let method = self.table::<MethodSpec>().get(rid).ok_or_else(invalid)?.method;
+                return self.call_site_signature(MetadataToken::Table(method.table, method.index));
+            }
+            MetadataToken::Table(TableKind::StandAloneSig, rid) => {
+                return match self.get_standalone_sig(self.get_stand_alone_sig(rid).ok_or_else(invalid)?)? {
+                    StandaloneSignature::Method(signature) => Ok(signature),
+                    StandaloneSignature::LocalVars(_) => Err(invalid()),
+                };
+            }
+            _ => None,
+        }.ok_or_else(invalid)?;
+
+        let blob = self.get_blob(signature).ok_or(LushError::InvalidSignature(signature.0))?;
+        MethodSignature::parse(blob, self.streams.metadata.context())
+            .map_err(|_| LushError::InvalidSignature(signature.0))
+    }
+}
+
+fn returns_value(return_type: &SigType) -> bool {
+    match return_type {
+        SigType::Void => false,
+        SigType::Modified { inner, .. } => returns_value(inner),
+        _ => true,
+    }
+}
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, io::BufRead};
+use std::{borrow::Cow, collections::HashMap};
 
 use super::*;
 
@@ -13,20 +13,25 @@ use super::*;
 /// GUID heap; and a stream header with name "#~" that points to the physical representation of a set of 
 /// tables. 
 /// 
+/// Images emitted for edit-and-continue, or by some compilers without optimizations, use an uncompressed
+/// "#-" stream instead of "#~". It has the same layout, but may contain pointer tables such as *MethodPtr*,
+/// see [`TableKind::pointer_table`]. Other streams, such as "#Pdb", are ignored.
+/// 
 /// [II.24.2.2]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=298
 pub struct Streams {
     pub strings: StringStream,
     pub user_strings: UserStringStream,
     pub blobs: BlobStream,
-    // TODO: GUIDStream
+    pub guids: GuidStream,
     pub metadata: MetadataStream,
 }
 
 impl Streams {
-    pub fn from(buffer: &mut Buffer, root_address: u64, headers: &Vec<StreamHeader>) -> Result<Streams, std::io::Error> {
+    pub fn from(buffer: &mut Cursor<impl AsRef<[u8]>>, root_address: u64, headers: &Vec<StreamHeader>) -> Result<Streams, std::io::Error> {
         let mut strings = None;
         let mut user_strings = None;
         let mut blobs = None;
+        let mut guids = None;
         let mut metadata = None;
 
         for header in headers {
@@ -35,16 +40,19 @@ impl Streams {
                 "#Strings" => strings = Some(StringStream::from(buffer, header)?),
                 "#US" => user_strings = Some(UserStringStream::from(buffer, header)?),
                 "#Blob" =>blobs = Some(BlobStream::from(buffer, header)?),
-                "#~" => metadata = Some(MetadataStream::from(buffer)?),
+                "#GUID" => guids = Some(GuidStream::from(buffer, header)?),
+                "#~" | "#-" => metadata = Some(MetadataStream::from(buffer)?),
                 _ => (),
             }
         }
 
+        // A missing heap is read as empty and reported by `ParseWarning::MissingStream`.
         Ok(Streams {
-            strings: strings.unwrap(),
-            user_strings: user_strings.unwrap(),
-            blobs: blobs.unwrap(),
-            metadata: metadata.unwrap(),
+            strings: strings.unwrap_or_default(),
+            user_strings: user_strings.unwrap_or_default(),
+            blobs: blobs.unwrap_or_default(),
+            guids: guids.unwrap_or_default(),
+            metadata: metadata.ok_or(LushError::MissingStream("#~"))?,
         })
     }
 }
@@ -69,24 +77,24 @@ impl Streams {
 /// 
 /// [II.24.2.3]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=298
 /// [II.22]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=235
-pub struct StringStream(HashMap<u32, String>);
+#[derive(Default)]
+pub struct StringStream(Vec<u8>);
 
 impl StringStream {
-    pub fn from(buffer: &mut Buffer, header: &StreamHeader) -> Result<StringStream, std::io::Error> {
-        let mut strings = HashMap::new();
-        let mut count = 0;
-        while count < header.size {
-            let mut string = Vec::new();
-            let read = buffer.read_until(0, &mut string)? as u32;
-            let string = String::from_utf8(string).unwrap();
-            strings.insert(count, string);
-            count += read;
-        }
-        Ok(StringStream(strings))
+    pub fn from(buffer: &mut Cursor<impl AsRef<[u8]>>, header: &StreamHeader) -> Result<StringStream, std::io::Error> {
+        let mut heap = vec![0u8; header.size as usize];
+        buffer.read_exact(&mut heap)?;
+        Ok(StringStream(heap))
     }
 
-    pub fn get(&self, index: u32) -> Option<&String> {
-        self.0.get(&index)
+    /// Decodes the string starting at byte offset `index`, up to (not including) the next null terminator.
+    ///
+    /// The offset doesn't have to be the start of an entry: compilers may point into the middle of a string
+    /// to share a common suffix. Invalid UTF-8 is replaced by `U+FFFD`.
+    pub fn get(&self, index: u32) -> Option<Cow<'_, str>> {
+        let bytes = self.0.get(index as usize..)?;
+        let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+        Some(String::from_utf8_lossy(&bytes[..end]))
     }
 }
 
@@ -97,7 +105,7 @@ impl StringStream {
 /// is reachable from any of the tables contains a valid 'blob'. Individual blobs are stored with their length 
 /// encoded in the first few bytes:
 /// 
-/// See [`BlobStream::read_length`].
+/// See [`read_blob_length`].
 /// 
 /// The first entry in both these heaps is the empty 'blob' that consists of the single byte 0x00. 
 ///
@@ -107,21 +115,22 @@ impl StringStream {
 /// 
 /// [II.24.2.4]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=298
 /// [II.22]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=235
-pub struct BlobStream(pub HashMap<u32, Vec<u8>>);
+#[derive(Default)]
+pub struct BlobStream(pub Vec<u8>);
 
 impl BlobStream {
-    pub fn from(buffer: &mut Buffer, header: &StreamHeader) -> Result<BlobStream, std::io::Error> {
-        let mut blobs = HashMap::new();
-        let mut count = 0;
-        while count < header.size {
-            let (length, bytes_read) = read_blob_length(buffer)?;
+    pub fn from(buffer: &mut Cursor<impl AsRef<[u8]>>, header: &StreamHeader) -> Result<BlobStream, std::io::Error> {
+        let mut heap = vec![0u8; header.size as usize];
+        buffer.read_exact(&mut heap)?;
+        Ok(BlobStream(heap))
+    }
 
-            let mut blob = vec![0u8; length];
-            buffer.read_exact(&mut blob).unwrap();
-            blobs.insert(count, blob);
-            count += bytes_read + length as u32;
-        }
-        Ok(BlobStream(blobs))
+    /// Returns the contents of the 'blob' whose length prefix starts at byte offset `index`, or `None` if
+    /// the prefix is invalid or the 'blob' extends past the end of the heap.
+    pub fn get(&self, index: BlobIndex) -> Option<&[u8]> {
+        let mut data = self.0.get(index.0 as usize..)?;
+        let (length, _) = read_compressed_u32(&mut data).ok()?;
+        data.get(..length as usize)
     }
 }
 
@@ -136,22 +145,8 @@ impl BlobStream {
 /// Returns the length of the 'blob' and the number of bytes read from the buffer.
 /// 
 /// [II.24.2.4]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=298
-fn read_blob_length(buffer: &mut Buffer) -> Result<(usize, u32), std::io::Error> {
-    let first = buffer.read_u8()?;
-
-    if first & 0b1000_0000 == 0 {
-        Ok((first as usize, 1))
-    } else if first & 0b1100_0000 == 0b1000_0000 {
-        let length = u16::from_be_bytes([first & 0b0011_1111, buffer.read_u8()?]);
-        Ok((length as usize, 2))
-    } else if first & 0b1110_0000 == 0b1100_0000 {
-        let mut bytes = [0u8; 3];
-        buffer.read_exact(&mut bytes).unwrap();
-        let length = u32::from_be_bytes([first & 0b0001_1111, bytes[0], bytes[1], bytes[2]]);
-        Ok((length as usize, 4))
-    } else {
-        Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid blob length"))
-    }
+fn read_blob_length(buffer: &mut Cursor<impl AsRef<[u8]>>) -> Result<(usize, u32), std::io::Error> {
+    read_compressed_u32(buffer).map(|(length, bytes_read)| (length as usize, bytes_read))
 }
 
 /// # [II.24.2.4] #US and #Blob heaps
@@ -171,29 +166,90 @@ fn read_blob_length(buffer: &mut Buffer) -> Result<(usize, u32), std::io::Error>
 /// 
 /// [II.24.2.4]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=298
 /// [II.22]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=235
+#[derive(Default)]
 pub struct UserStringStream(pub HashMap<u32, Vec<u16>>);
 
 impl UserStringStream {
-    pub fn from(buffer: &mut Buffer, header: &StreamHeader) -> Result<UserStringStream, std::io::Error> {
+    pub fn from(buffer: &mut Cursor<impl AsRef<[u8]>>, header: &StreamHeader) -> Result<UserStringStream, std::io::Error> {
         let mut strings = HashMap::new();
         let mut count = 0;
         while count < header.size {
             let (length, bytes_read) = read_blob_length(buffer)?;
 
-            // Read UTF-16 string
-            let mut string = vec![0u16; length >> 2];
-            for char in string.iter_mut().take(length >> 1) {
+            // Read UTF-16 string, the final byte is the terminal flag described above
+            let mut string = vec![0u16; length.saturating_sub(1) / 2];
+            for char in string.iter_mut() {
                 *char = buffer.read_u16::<LittleEndian>()?;
             }
+            if length % 2 == 1 {
+                buffer.read_u8()?;
+            }
 
             strings.insert(count, string);
             count += bytes_read + length as u32;
         }
         Ok(UserStringStream(strings))
     }
+
+    /// Returns the string starting at byte offset `index`, with unpaired surrogates replaced by `U+FFFD`.
+    pub fn get(&self, index: u32) -> Option<String> {
+        self.0.get(&index).map(|string| String::from_utf16_lossy(string))
+    }
 }
 
-// TODO: Implement GUIDStream
+/// # [II.24.2.5] #GUID heap
+///
+/// The "#GUID" header points to a sequence of 128-bit GUIDs. There might be unreachable GUIDs stored in
+/// the stream.
+///
+/// # [II.22] Metadata logical format: tables
+///
+/// [...]
+///
+/// The Guid heap is an array of GUIDs, each 16 bytes wide. Its first element is numbered 1, its second 2,
+/// and so on.
+///
+/// [II.24.2.5]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=299
+/// [II.22]: https://www.ecma-international.org/wp-content/uploads/ECMA-335_6th_edition_june_2012.pdf#page=235
+#[derive(Default)]
+pub struct GuidStream(pub Vec<[u8; 16]>);
+
+impl GuidStream {
+    pub fn from(buffer: &mut Cursor<impl AsRef<[u8]>>, header: &StreamHeader) -> Result<GuidStream, std::io::Error> {
+        let mut guids = Vec::with_capacity((header.size / 16) as usize);
+        for _ in 0..header.size / 16 {
+            let mut guid = [0u8; 16];
+            buffer.read_exact(&mut guid)?;
+            guids.push(guid);
+        }
+        Ok(GuidStream(guids))
+    }
+
+    /// Returns the GUID at the given 1-based index, or `None` for the null index 0.
+    pub fn get(&self, index: GuidIndex) -> Option<[u8; 16]> {
+        if index.0 == 0 {
+            return None;
+        }
+        self.0.get((index.0 - 1) as usize).copied()
+    }
+
+    /// Iterates every GUID in the heap along with its 1-based index.
+    pub fn iter(&self) -> impl Iterator<Item = (GuidIndex, [u8; 16])> + '_ {
+        self.0.iter().enumerate().map(|(i, guid)| (GuidIndex(i as u32 + 1), *guid))
+    }
+}
+
+/// Formats a GUID as `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`, the way .NET and `ildasm` do. The first three
+/// groups are stored little-endian, the last two as plain bytes.
+pub fn format_guid(guid: [u8; 16]) -> String {
+    format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]),
+        u16::from_le_bytes([guid[4], guid[5]]),
+        u16::from_le_bytes([guid[6], guid[7]]),
+        guid[8], guid[9], guid[10], guid[11], guid[12], guid[13], guid[14], guid[15],
+    )
+}
 
 /// # [II.24.2.6] #~ stream 
 /// 
@@ -212,6 +268,9 @@ impl UserStringStream {
 /// | 24       | 4**n*  | Rows         | Array of n 4-byte unsigned integers indicating the number of rows for each present table. |
 /// | 24+4**n* |        | Tables       | The sequence of physical tables. |
 /// 
+/// When [`HeapSizes::EXTRA_DATA`] is set, which only happens in "#-" streams, a 4-byte *ExtraData* value sits
+/// between the Rows array and the Tables.
+/// 
 /// The Valid field is a 64-bit bitvector that has a specific bit set for each table that is stored in the stream; 
 /// the mapping of tables to indexes is given at the start of §II.22. For example when the `DeclSecurity` 
 /// table is present in the logical metadata, bit `0x0e` should be set in the Valid vector. It is invalid to 
@@ -232,17 +291,21 @@ pub struct MetadataStream {
     pub valid: u64,
     pub sorted: u64,
     pub rows: Vec<u32>,
+    /// The value following the Rows array, present if [`HeapSizes::has_extra_data`].
+    pub extra_data: Option<u32>,
     pub tables: HashMap<TableKind, Table>,
+    context: TableDecodeContext,
 }
 
 impl MetadataStream {
-    pub fn from(buffer: &mut Buffer) -> Result<MetadataStream, std::io::Error> {
+    pub fn from(buffer: &mut Cursor<impl AsRef<[u8]>>) -> Result<MetadataStream> {
         buffer.read_u32::<LittleEndian>()?; // Reserved
 
         let major_version = buffer.read_u8()?;
         let minor_version = buffer.read_u8()?;
-        assert_eq!(major_version, 2, "Invalid major version");
-        assert_eq!(minor_version, 0, "Invalid minor version");
+        if major_version != 2 || minor_version != 0 {
+            return Err(LushError::UnsupportedVersion { major: major_version, minor: minor_version });
+        }
         
         let heap_sizes = HeapSizes::from(buffer.read_u8()?);
 
@@ -254,7 +317,7 @@ impl MetadataStream {
         let mut rows = Vec::new();
 
         let number_of_tables = valid.count_ones();
-        let table_kinds = TableKind::from_bitmask(valid);
+        let table_kinds = TableKind::from_bitmask(valid)?;
 
         for i in 0..number_of_tables {
             let count = buffer.read_u32::<LittleEndian>()?;
@@ -262,15 +325,28 @@ impl MetadataStream {
             row_count.insert(table_kinds[i as usize], count);
         }
 
+        let extra_data = match heap_sizes.has_extra_data() {
+            true => Some(buffer.read_u32::<LittleEndian>()?),
+            false => None,
+        };
+
         let mut tables = HashMap::new();
         let context = TableDecodeContext::new(row_count, heap_sizes);
 
         for kind in table_kinds.iter() {
             let row_count = context.get_row_count(*kind);
-            let mut table = Vec::with_capacity(row_count as usize);
+            let size = row_count as usize * kind.row_size(&context);
+            // Check the row count against the stream before allocating for it.
+            if size as u64 > (buffer.get_ref().as_ref().len() as u64).saturating_sub(buffer.position()) {
+                return Err(LushError::UnexpectedEof);
+            }
+            let mut data = vec![0u8; size];
+            buffer.read_exact(&mut data)?;
 
+            let mut rows = Buffer::new(data);
+            let mut table = Vec::with_capacity(row_count as usize);
             for _ in 0..row_count {
-                table.push(Row::read(buffer, *kind, &context)?);
+                table.push(Row::read(&mut rows, *kind, &context)?);
             }
 
             tables.insert(*kind, table);
@@ -283,11 +359,73 @@ impl MetadataStream {
             valid,
             sorted,
             rows,
+            extra_data,
             tables,
+            context,
         })
     }
 
     pub fn get_table(&self, kind: TableKind) -> &Table {
         self.tables.get(&kind).unwrap()
     }
+
+    /// Returns the kinds of the tables present in the image, in table number order.
+    pub fn present_tables(&self) -> Vec<TableKind> {
+        let mut kinds: Vec<TableKind> = self.tables.keys().copied().collect();
+        kinds.sort_by_key(|kind| u8::from(*kind));
+        kinds
+    }
+
+    /// Whether the table of the given kind is flagged in the *Sorted* bit vector, in which case lookups by
+    /// its primary key can use a binary search.
+    pub fn is_sorted(&self, kind: TableKind) -> bool {
+        self.sorted & (1 << u8::from(kind)) != 0
+    }
+
+    /// Returns the tables flagged in the *Sorted* bit vector whose rows aren't in fact ordered by their
+    /// primary key, see [`Row::sort_key`]. Tables without a primary key, or whose rows can't be decoded,
+    /// aren't checked.
+    pub fn verify_sorted(&self) -> Vec<TableKind> {
+        self.present_tables()
+            .into_iter()
+            .filter(|kind| self.is_sorted(*kind))
+            .filter(|kind| {
+                self.tables[kind].windows(2).any(|pair| match (pair[0].sort_key(), pair[1].sort_key()) {
+                    (Some(previous), Some(next)) => previous > next,
+                    _ => false,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the table of the given kind is flagged in the *Valid* bit vector, i.e. present in the image.
+    pub fn is_present(&self, kind: TableKind) -> bool {
+        self.valid & (1 << u8::from(kind)) != 0
+    }
+
+    /// Returns the table of the given kind, or `None` if it isn't present in the image.
+    pub fn try_get_table(&self, kind: TableKind) -> Option<&Table> {
+        self.tables.get(&kind)
+    }
+
+    pub fn heap_sizes(&self) -> HeapSizes {
+        self.heap_sizes
+    }
+
+    /// Returns the row counts and index sizes the tables were decoded with.
+    pub fn context(&self) -> &TableDecodeContext {
+        &self.context
+    }
+
+    /// Returns the width in bytes (2 or 4) that was used to decode coded indices of the given kind.
+    pub fn coded_index_size(&self, tag: CodedIndexTag) -> u8 {
+        self.context.get_coded_index_size(tag)
+    }
+
+    /// Returns the width in bytes (2 or 4) that was used to decode each kind of coded index.
+    pub fn coded_index_sizes(&self) -> Vec<(CodedIndexTag, u8)> {
+        CodedIndexTag::iter()
+            .map(|(tag, _)| (*tag, self.context.get_coded_index_size(*tag)))
+            .collect()
+    }
 }
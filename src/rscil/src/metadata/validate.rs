@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+
+use super::*;
+
+/// Something odd about an image that didn't stop it from being parsed, see [`PeImage::warnings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// A "shall" rule of the spec is violated, see [`ParseOptions::strict`] to turn these into errors.
+    SpecViolation(String),
+    /// A heap stream is absent and was treated as empty.
+    MissingStream(&'static str),
+    /// A stream with a name other than the five defined by §II.24.2.2, or the uncompressed "#-" tables
+    /// stream, was skipped.
+    UnknownStream(String),
+}
+
+impl PeImage {
+    /// Collects every [`ParseWarning`] about the image.
+    pub(crate) fn collect_warnings(&self) -> Vec<ParseWarning> {
+        let mut warnings: Vec<ParseWarning> = self.spec_violations()
+            .into_iter()
+            .map(ParseWarning::SpecViolation)
+            .collect();
+
+        let headers = &self.metadata_header.stream_headers;
+        for heap in ["#Strings", "#US", "#Blob", "#GUID"] {
+            if !headers.iter().any(|header| header.name == heap) {
+                warnings.push(ParseWarning::MissingStream(heap));
+            }
+        }
+        for header in headers {
+            if !matches!(header.name.as_str(), "#~" | "#-" | "#Strings" | "#US" | "#Blob" | "#GUID") {
+                warnings.push(ParseWarning::UnknownStream(header.name.clone()));
+            }
+        }
+
+        warnings
+    }
+
+    /// Checks the "shall" rules of the spec that parsing doesn't depend on, returning a description of
+    /// each one the image violates.
+    pub(crate) fn spec_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        // II.25.2.2 PE file header: no symbol table, and an executable image without stripped relocations
+        let pe_header = &self.pe_header;
+        if pe_header.pointer_to_symbol_table != 0 || pe_header.number_of_symbols != 0 {
+            violations.push("PE header has a symbol table, shall have none".to_string());
+        }
+        if pe_header.characteristics.contains(FileCharacteristics::IMAGE_FILE_RELOCS_STRIPPED) {
+            violations.push("IMAGE_FILE_RELOCS_STRIPPED is set, shall be zero".to_string());
+        }
+        if !pe_header.characteristics.contains(FileCharacteristics::IMAGE_FILE_EXECUTABLE_IMAGE) {
+            violations.push("IMAGE_FILE_EXECUTABLE_IMAGE is clear, shall be one".to_string());
+        }
+
+        // II.25.2.3.2 PE header Windows NT-specific fields
+        let nt = &self.optional_header.nt_specific_fields;
+        if !nt.image.is_multiple_of(0x10000) {
+            violations.push(format!("image base is 0x{:X}, shall be a multiple of 0x10000", nt.image));
+        }
+        if nt.section_alignment <= nt.file_alignment {
+            violations.push(format!("section alignment 0x{:X} shall be greater than file alignment 0x{:X}", nt.section_alignment, nt.file_alignment));
+        }
+        if nt.reserved != 0 {
+            violations.push(format!("NT-specific Reserved field is 0x{:X}, shall be 0", nt.reserved));
+        }
+        if nt.dll_flags & 0x100f != 0 {
+            violations.push(format!("DLL flags are 0x{:X}, bits 0x100F shall be zero", nt.dll_flags));
+        }
+        if nt.loader_flags != 0 {
+            violations.push(format!("loader flags are 0x{:X}, shall be 0", nt.loader_flags));
+        }
+        if nt.number_of_data_directories != 0x10 {
+            violations.push(format!("image has {} data directories, shall have 16", nt.number_of_data_directories));
+        }
+
+        // II.24.2.1 Metadata root: Reserved and Flags are always 0
+        if self.metadata_header.reserved != 0 {
+            violations.push(format!("metadata root Reserved field is 0x{:X}, shall be 0", self.metadata_header.reserved));
+        }
+        if self.metadata_header.flags != 0 {
+            violations.push(format!("metadata root Flags field is 0x{:X}, shall be 0", self.metadata_header.flags));
+        }
+
+        // II.25.3.3.1 Runtime flags: a strong name signed image shall have a StrongNameSignature
+        if self.cli_header.flags.contains(RuntimeFlags::COMIMAGE_FLAGS_STRONGNAMESIGNED) && self.cli_header.strong_name_signature.size == 0 {
+            violations.push("image is flagged as strong name signed, but has no StrongNameSignature".to_string());
+        }
+
+        // II.22.30 Module: the Module table shall contain one and only one row
+        let modules = self.table::<Module>().len();
+        if modules != 1 {
+            violations.push(format!("Module table has {} rows, shall have exactly 1", modules));
+        }
+
+        // II.22.2 Assembly: the Assembly table shall contain zero or one row
+        let assemblies = self.table::<Assembly>().len();
+        if assemblies > 1 {
+            violations.push(format!("Assembly table has {} rows, shall have at most 1", assemblies));
+        }
+
+        // II.22 Metadata logical format: tables flagged as sorted shall be ordered by their primary key
+        for kind in self.streams.metadata.verify_sorted() {
+            violations.push(format!("{} table is flagged as sorted, but isn't ordered by its primary key", kind.name()));
+        }
+
+        // II.22.8 ClassLayout: AutoLayout types shall not own any rows in the ClassLayout table
+        for row in self.table::<ClassLayout>() {
+            let parent = self.get_type_def(row.parent.index);
+            if parent.is_some_and(|type_def| type_def.layout() == Some(TypeLayout::Auto)) {
+                violations.push(format!("ClassLayout row {} belongs to auto layout TypeDef[{}]", row.index, row.parent.index));
+            }
+        }
+
+        // II.22.16 FieldLayout: the type that owns the field shall have its ExplicitLayout flag set
+        if !self.table::<FieldLayout>().is_empty() {
+            let explicit_fields: HashSet<u32> = self.table::<TypeDef>().iter()
+                .filter(|type_def| type_def.layout() == Some(TypeLayout::Explicit))
+                .flat_map(|type_def| self.list_run(type_def, |row| row.field_list))
+                .collect();
+            for row in self.table::<FieldLayout>() {
+                if !explicit_fields.contains(&row.field.index) {
+                    violations.push(format!("FieldLayout row {} belongs to Field[{}], which isn't owned by an explicit layout type", row.index, row.field.index));
+                }
+            }
+        }
+
+        violations
+    }
+}
@@ -0,0 +1,61 @@
+use std::marker::PhantomData;
+
+use super::*;
+
+static EMPTY_TABLE: Table = Vec::new();
+
+/// A typed view over a single metadata table, see [`PeImage::table`].
+///
+/// Rows are addressed by their 1-based row index (RID), matching how tables are indexed
+/// from tokens and other tables.
+pub struct TableView<'a, T: TableRow> {
+    rows: &'a Table,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<'a, T: TableRow> TableView<'a, T> {
+    pub fn new(rows: &'a Table) -> Self {
+        TableView { rows, _marker: PhantomData }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(&EMPTY_TABLE)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the row with the given 1-based index, or `None` if `rid` is 0 or out of range.
+    pub fn get(&self, rid: u32) -> Option<&'a T> {
+        if rid == 0 {
+            return None;
+        }
+        self.rows.get((rid - 1) as usize).and_then(T::from_row)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + 'a {
+        self.rows.iter().filter_map(T::from_row)
+    }
+}
+
+impl<T: TableRow> Clone for TableView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: TableRow> Copy for TableView<'_, T> {}
+
+impl<'a, T: TableRow + 'a> IntoIterator for TableView<'a, T> {
+    type Item = &'a T;
+    type IntoIter = std::iter::FilterMap<std::slice::Iter<'a, Row>, fn(&'a Row) -> Option<&'a T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.rows.iter().filter_map(T::from_row)
+    }
+}